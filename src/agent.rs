@@ -0,0 +1,26 @@
+use crate::position::{Move, Position};
+use std::sync::atomic::AtomicBool;
+use std::time::Instant;
+
+// Anything that can take a seat in `Game`: the built-in AIs, an engine behind a protocol,
+// a network opponent, or a record being played back.
+// `choose_move` runs in a background thread, so it may block until `deadline`.
+// It should return early when `stop` is set, because its answer will be thrown away.
+pub trait Agent: Send {
+    fn name(&self) -> String;
+
+    // called with every new position of the game, including the ones after its own moves
+    fn observe(&mut self, _position: &Position) {}
+
+    // `None` if it has nothing to play
+    fn choose_move(&mut self, position: &Position, deadline: Instant, stop: &AtomicBool) -> Option<Move>;
+
+    // the moves it expected after its last choice, starting with that choice
+    fn principal_variation(&self) -> Vec<Move> {
+        vec![]
+    }
+
+    // Called while the opponent thinks on `position`, until `stop` is set.
+    // An agent can prepare its next answer here, like filling its transposition table.
+    fn ponder(&mut self, _position: &Position, _stop: &AtomicBool) {}
+}
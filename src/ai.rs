@@ -1 +1,27 @@
-// TODO
\ No newline at end of file
+pub mod agents;
+pub mod arena;
+pub mod bench;
+pub mod book;
+pub mod endgame;
+pub mod eval;
+pub mod mcts;
+pub mod personality;
+pub mod review;
+pub mod search;
+pub mod strength;
+pub mod tt;
+pub mod tune;
+
+use crate::engine::global::GLOBAL_ENV;
+use std::thread;
+
+// `GLOBAL_ENV.ai_threads`, or the number of cores if it's not set
+pub fn thread_count() -> usize {
+    match unsafe { GLOBAL_ENV.ai_threads } {
+        0 => thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        n => n,
+    }
+}
+
+// 2^20 slots: 16 MiB
+pub const TT_BITS: u32 = 20;
@@ -0,0 +1,154 @@
+use super::book::{random_good_move, Book};
+use super::mcts::{Mcts, MctsConfig};
+use super::personality::Personality;
+use super::strength::Strength;
+use super::search::{search, SearchConfig};
+use super::tt::TranspositionTable;
+use super::TT_BITS;
+use crate::agent::Agent;
+use crate::position::{Move, Position};
+use macroquad::rand::gen_range;
+use std::sync::{Arc, atomic::AtomicBool};
+use std::time::Instant;
+
+// alpha-beta with the opening book and the endgame solver
+pub struct AlphaBetaAgent {
+    config: SearchConfig,
+    tt: TranspositionTable,  // kept between the moves, so that it can reuse its previous searches
+    book: Option<Arc<Book>>,
+    randomize_book: bool,
+    personality: Personality,
+    strength: Option<Strength>,  // `None` for full strength
+    pv: Vec<Move>,  // of the last search
+}
+
+impl AlphaBetaAgent {
+    pub fn new(config: SearchConfig) -> Self {
+        AlphaBetaAgent {
+            config,
+            tt: TranspositionTable::new(TT_BITS),
+            book: None,
+            randomize_book: false,
+            personality: Personality::Balanced,
+            strength: None,
+            pv: vec![],
+        }
+    }
+
+    pub fn with_book(mut self, book: Arc<Book>, randomize: bool) -> Self {
+        self.book = Some(book);
+        self.randomize_book = randomize;
+        self
+    }
+
+    // the book is made of balanced games, so the other personalities don't use it
+    pub fn with_personality(mut self, personality: Personality) -> Self {
+        self.config.weights = personality.apply(&self.config.weights);
+        self.personality = personality;
+        self
+    }
+
+    pub fn with_strength(mut self, strength: Strength) -> Self {
+        self.config.max_depth = self.config.max_depth.min(strength.max_depth);
+        self.strength = Some(strength);
+        self
+    }
+}
+
+impl Agent for AlphaBetaAgent {
+    fn name(&self) -> String {
+        let name = match self.personality {
+            Personality::Balanced => String::from("cpu"),
+            personality => format!("{} cpu", personality.name()),
+        };
+
+        match self.strength {
+            Some(strength) => format!("{} lv{}", name, strength.level),
+            None => name,
+        }
+    }
+
+    fn choose_move(&mut self, position: &Position, deadline: Instant, stop: &AtomicBool) -> Option<Move> {
+        self.pv = vec![];
+
+        let blunder = self.strength.map(|strength| gen_range(0.0, 1.0) < strength.blunder_chance).unwrap_or(false);
+
+        if blunder {
+            // scored shallowly, because it looks at every legal move, and the time is shared between the moves
+            let margin = self.strength.map(|strength| strength.blunder_margin).unwrap_or(0);
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let config = SearchConfig {
+                max_depth: self.config.max_depth.min(BLUNDER_DEPTH),
+                time_limit: Some(remaining / position.legal_moves().len().max(1) as u32),
+                ..self.config.clone()
+            };
+
+            let mv = random_good_move(position, &config, &self.tt, margin, stop);
+            self.pv = mv.into_iter().collect();
+
+            return mv;
+        }
+
+        let book = self.book.as_ref().filter(|_| self.personality == Personality::Balanced);
+
+        if let Some(mv) = book.and_then(|book| book.pick(position, self.randomize_book)) {
+            self.pv = vec![mv];
+            return Some(mv);
+        }
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let config = SearchConfig {
+            time_limit: Some(self.config.time_limit.map(|limit| limit.min(remaining)).unwrap_or(remaining)),
+            ..self.config.clone()
+        };
+
+        let result = search(position, &config, &self.tt, stop);
+        self.pv = result.pv;
+
+        result.best_move
+    }
+
+    fn principal_variation(&self) -> Vec<Move> {
+        self.pv.clone()
+    }
+
+    // The search from the opponent's view fills the table with the answers to all of its moves.
+    // It runs until it's stopped, unless the depth is limited.
+    fn ponder(&mut self, position: &Position, stop: &AtomicBool) {
+        let config = SearchConfig {
+            time_limit: None,
+            ..self.config.clone()
+        };
+
+        search(position, &config, &self.tt, stop);
+    }
+}
+
+pub struct MctsAgent {
+    mcts: Mcts,
+}
+
+impl MctsAgent {
+    pub fn new(config: MctsConfig) -> Self {
+        MctsAgent {
+            mcts: Mcts::new(config),
+        }
+    }
+}
+
+impl Agent for MctsAgent {
+    fn name(&self) -> String {
+        String::from("mcts")
+    }
+
+    fn choose_move(&mut self, position: &Position, deadline: Instant, stop: &AtomicBool) -> Option<Move> {
+        self.mcts.search(position, Some(deadline), stop)
+    }
+
+    // the playouts from the opponent's position grow the subtrees that `choose_move` keeps
+    fn ponder(&mut self, position: &Position, stop: &AtomicBool) {
+        self.mcts.search(position, None, stop);
+    }
+}
+
+const BLUNDER_DEPTH: u8 = 2;
@@ -0,0 +1,86 @@
+use crate::agent::Agent;
+use crate::position::{Move, Position};
+use macroquad::rand::gen_range;
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+// Plays `games` games between the two agents. Every opening is a few random pawn steps,
+// and it's played twice with the colors swapped, so that neither agent gets a better start.
+// The agents are limited by their own configs, not by the arena.
+pub fn run(engines: &mut [Box<dyn Agent>; 2], games: usize) {
+    let stop = AtomicBool::new(false);
+
+    let mut wins = [0, 0];
+    let mut draws = 0;
+    let mut think_time = [Duration::ZERO; 2];
+    let mut opening = random_opening();
+
+    for game in 0..games {
+        if game % 2 == 0 && game > 0 {
+            opening = random_opening();
+        }
+
+        // engine `first` plays player 1
+        let first = game % 2;
+        let mut position = opening.clone();
+        let mut plies = 0;
+
+        while position.winner().is_none() && plies < MAX_GAME_LENGTH {
+            let engine = if position.player1_turn { first } else { 1 - first };
+            let started_at = Instant::now();
+            let mv = engines[engine].choose_move(&position, started_at + MOVE_TIME_LIMIT, &stop);
+            think_time[engine] += Instant::now().duration_since(started_at);
+
+            match mv {
+                Some(mv) if position.is_legal(mv) => {
+                    position.apply(mv);
+                    plies += 1;
+
+                    for engine in engines.iter_mut() {
+                        engine.observe(&position);
+                    }
+                },
+                _ => {
+                    println!("{} made an illegal move: {:?}", engines[engine].name(), mv);
+                    break;
+                },
+            }
+        }
+
+        let result = match position.winner() {
+            Some(player1_won) => {
+                let winner = if player1_won { first } else { 1 - first };
+                wins[winner] += 1;
+                engines[winner].name()
+            },
+            None => {
+                draws += 1;
+                "draw".to_string()
+            },
+        };
+
+        println!("game {}/{}: p1 {}, p2 {}, {} plies, winner: {}", game + 1, games, engines[first].name(), engines[1 - first].name(), plies, result);
+    }
+
+    for (index, engine) in engines.iter().enumerate() {
+        println!("{}: {} wins, {:.1}s thinking", engine.name(), wins[index], think_time[index].as_secs_f64());
+    }
+
+    println!("draws: {}", draws);
+}
+
+fn random_opening() -> Position {
+    let mut position = Position::new();
+
+    for _ in 0..OPENING_PLIES {
+        let steps = position.valid_steps();
+        let (x, y) = steps[gen_range(0, steps.len())];
+        position.apply(Move::Step(x, y));
+    }
+
+    position
+}
+
+const OPENING_PLIES: usize = 4;
+const MAX_GAME_LENGTH: usize = 300;
+const MOVE_TIME_LIMIT: Duration = Duration::from_secs(3600);
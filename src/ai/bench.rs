@@ -0,0 +1,86 @@
+use super::search::{search, SearchConfig, SearchResult};
+use super::tt::TranspositionTable;
+use super::TT_BITS;
+use crate::position::Position;
+use std::sync::atomic::AtomicBool;
+
+// searches a fixed set of positions to a fixed depth with 1, 2, 4, ... threads,
+// and prints the nodes per second and how well the time to depth scales
+pub fn run(max_threads: usize, depth: u8) {
+    let positions = bench_positions();
+    let mut thread_counts = vec![];
+    let mut threads = 1;
+
+    while threads < max_threads {
+        thread_counts.push(threads);
+        threads *= 2;
+    }
+
+    thread_counts.push(max_threads.max(1));
+
+    println!("bench: {} positions, depth {}", positions.len(), depth);
+    println!("{:>7} {:>12} {:>9} {:>12} {:>8} {:>10} {:>10}", "threads", "nodes", "time(s)", "nodes/s", "speedup", "efficiency", "nps scale");
+
+    let mut base: Option<(f64, f64)> = None;  // (time, nps) with one thread
+
+    for threads in thread_counts {
+        let config = SearchConfig {
+            threads,
+            max_depth: depth,
+            ..SearchConfig::default()
+        };
+        let mut nodes = 0;
+        let mut seconds = 0.0;
+
+        for (index, position) in positions.iter().enumerate() {
+            let result = search_fresh(position, &config);
+            nodes += result.nodes;
+            seconds += result.elapsed.as_secs_f64();
+
+            // with a single thread, these lines must be the same on every run
+            if threads == 1 {
+                println!("  #{}: depth {}, score {}, pv {:?}", index, result.depth, result.score, result.pv);
+            }
+        }
+
+        let nps = nodes as f64 / seconds.max(1e-9);
+        let (base_seconds, base_nps) = *base.get_or_insert((seconds, nps));
+        let speedup = base_seconds / seconds.max(1e-9);
+
+        println!(
+            "{:>7} {:>12} {:>9.3} {:>12.0} {:>8.2} {:>9.1}% {:>9.1}%",
+            threads, nodes, seconds, nps, speedup,
+            speedup / threads as f64 * 100.0,
+            nps / (base_nps * threads as f64) * 100.0,
+        );
+    }
+}
+
+fn search_fresh(position: &Position, config: &SearchConfig) -> SearchResult {
+    let tt = TranspositionTable::new(TT_BITS);
+    search(position, config, &tt, &AtomicBool::new(false))
+}
+
+// the opening position, and the positions after 8 and 16 plies of a shallow self-play
+fn bench_positions() -> Vec<Position> {
+    let mut result = vec![];
+    let mut position = Position::new();
+    let config = SearchConfig {
+        threads: 1,
+        max_depth: 2,
+        ..SearchConfig::default()
+    };
+
+    for ply in 0..=16 {
+        if ply % 8 == 0 {
+            result.push(position.clone());
+        }
+
+        match search_fresh(&position, &config).best_move {
+            Some(mv) if position.winner().is_none() => { position.apply(mv); },
+            _ => { break; },
+        }
+    }
+
+    result
+}
@@ -0,0 +1,219 @@
+use super::eval::{Weights, MATE};
+use super::search::{search, SearchConfig};
+use super::tt::TranspositionTable;
+use super::TT_BITS;
+use crate::engine::file_io::{is_dir, read_dir, read_string, write_to_file};
+use crate::position::{Move, Position};
+use crate::record::GameRecord;
+use macroquad::rand::gen_range;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Opening book: position hash -> weighted moves. The file is plain text, one move per line:
+//
+// # Quoridor opening book: position hash, move, weight
+// 3c1a2b7e9d5f0a18 b5 12
+pub struct Book {
+    entries: HashMap<u64, Vec<(Move, u32)>>,
+}
+
+impl Book {
+    pub fn new() -> Self {
+        Book {
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let s = read_string(path).map_err(|_| format!("Failed to open file: {}", path))?;
+        let mut book = Book::new();
+
+        for line in s.lines() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+
+            match (tokens.len(), tokens.first().and_then(|hash| u64::from_str_radix(hash, 16).ok()), tokens.get(1).and_then(|mv| Move::from_notation(mv)), tokens.get(2).and_then(|weight| weight.parse().ok())) {
+                (3, Some(hash), Some(mv), Some(weight)) => { book.add(hash, mv, weight); },
+                _ => { return Err(format!("{}: invalid line in an opening book: `{}`", path, line)); },
+            }
+        }
+
+        Ok(book)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let mut hashes: Vec<&u64> = self.entries.keys().collect();
+        hashes.sort();
+
+        let mut lines = vec!["# Quoridor opening book: position hash, move, weight".to_string()];
+
+        for hash in hashes {
+            for (mv, weight) in self.entries[hash].iter() {
+                lines.push(format!("{:016x} {} {}", hash, mv.to_notation(), weight));
+            }
+        }
+
+        write_to_file(path, (lines.join("\n") + "\n").as_bytes()).map_err(|_| format!("Failed to write file: {}", path))
+    }
+
+    pub fn add(&mut self, hash: u64, mv: Move, weight: u32) {
+        let moves = self.entries.entry(hash).or_default();
+
+        match moves.iter_mut().find(|(m, _)| *m == mv) {
+            Some((_, w)) => { *w += weight; },
+            None => { moves.push((mv, weight)); },
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    // the heaviest move, or a random one with a probability proportional to its weight
+    pub fn pick(&self, position: &Position, randomize: bool) -> Option<Move> {
+        let moves: Vec<(Move, u32)> = self.entries.get(&position.hash())?.iter().filter(
+            |(mv, weight)| *weight > 0 && position.is_legal(*mv)
+        ).copied().collect();
+
+        if !randomize {
+            return moves.iter().max_by_key(|(_, weight)| *weight).map(|(mv, _)| *mv);
+        }
+
+        let total: u32 = moves.iter().map(|(_, weight)| weight).sum();
+
+        if total == 0 {
+            return None;
+        }
+
+        let mut ticket = gen_range(0, total);
+
+        for (mv, weight) in moves.iter() {
+            if ticket < *weight {
+                return Some(*mv);
+            }
+
+            ticket -= weight;
+        }
+
+        None
+    }
+
+    // the first `max_plies` moves of the winner of each game, weighted by how often they were played
+    pub fn add_records(&mut self, records: &[GameRecord], max_plies: usize) {
+        for record in records.iter() {
+            let winner = match record.winner {
+                Some(winner) => winner,
+                None => { continue; },
+            };
+
+            let positions = match record.replay() {
+                Ok(positions) => positions,
+                Err(_) => { continue; },
+            };
+
+            for (position, mv) in positions.iter().zip(record.moves.iter()).take(max_plies) {
+                if position.player1_turn == winner {
+                    self.add(position.hash(), *mv, 1);
+                }
+            }
+        }
+    }
+}
+
+// Plays `games` games against itself. For the first `max_plies` plies, it picks a random move
+// among the ones that are within `RANDOM_MARGIN` of the best, so that the games do not repeat.
+pub fn self_play(games: usize, depth: u8, max_plies: usize, weights: &Weights) -> Vec<GameRecord> {
+    let tt = TranspositionTable::new(TT_BITS);
+    let stop = AtomicBool::new(false);
+    let config = SearchConfig {
+        threads: 1,
+        max_depth: depth,
+        weights: weights.clone(),
+        ..SearchConfig::default()
+    };
+    let mut result = Vec::with_capacity(games);
+
+    for game in 0..games {
+        let mut record = GameRecord::new();
+        let mut position = Position::new();
+
+        while position.winner().is_none() && record.moves.len() < MAX_GAME_LENGTH {
+            let mv = if record.moves.len() < max_plies {
+                random_good_move(&position, &config, &tt, RANDOM_MARGIN, &stop)
+            } else {
+                search(&position, &config, &tt, &stop).best_move
+            };
+
+            match mv {
+                Some(mv) => {
+                    position.apply(mv);
+                    record.moves.push(mv);
+                },
+                None => { break; },
+            }
+        }
+
+        record.winner = position.winner();
+        println!("self-play {}/{}: {} plies, winner: {}", game + 1, games, record.moves.len(), match record.winner {
+            Some(true) => "p1",
+            Some(false) => "p2",
+            None => "-",
+        });
+        result.push(record);
+    }
+
+    result
+}
+
+// a random move among the ones within `margin` of the best, each scored by a search one ply shallower than `config`
+// `None` once `stop` is set
+pub fn random_good_move(position: &Position, config: &SearchConfig, tt: &TranspositionTable, margin: i32, stop: &AtomicBool) -> Option<Move> {
+    let child_config = SearchConfig {
+        max_depth: config.max_depth.saturating_sub(1).max(1),
+        ..config.clone()
+    };
+    let mut scored = vec![];
+
+    for mv in position.legal_moves() {
+        let mut next = position.clone();
+        next.apply(mv);
+
+        let score = if next.winner().is_some() {
+            MATE
+        } else {
+            -search(&next, &child_config, tt, stop).score
+        };
+
+        if stop.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        scored.push((mv, score));
+    }
+
+    let best = scored.iter().map(|(_, score)| *score).max()?;
+    let candidates: Vec<Move> = scored.into_iter().filter(|(_, score)| *score >= best - margin).map(|(mv, _)| mv).collect();
+
+    candidates.get(gen_range(0, candidates.len())).copied()
+}
+
+// every record in `path`, which is either a record file or a directory of them
+pub fn load_records(path: &str) -> Result<Vec<GameRecord>, String> {
+    if !is_dir(path) {
+        return Ok(vec![GameRecord::load(path)?]);
+    }
+
+    let mut paths = read_dir(path).map_err(|_| format!("Failed to read directory: {}", path))?;
+    paths.sort();
+
+    paths.iter().map(|path| GameRecord::load(path)).collect()
+}
+
+const RANDOM_MARGIN: i32 = 40;
+const MAX_GAME_LENGTH: usize = 200;
+pub const BOOK_PATH: &str = "book.txt";
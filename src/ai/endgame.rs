@@ -0,0 +1,419 @@
+use crate::position::{Move, Position};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+// from the view of the player to move, with the number of plies until the game ends
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Outcome {
+    Win(u8),
+    Loss(u8),
+    Draw,
+}
+
+impl Outcome {
+    // the winner's own moves, for messages like "forced win in 3"
+    pub fn moves_to_end(&self) -> u8 {
+        match self {
+            Outcome::Win(plies) | Outcome::Loss(plies) => plies.div_ceil(2),
+            Outcome::Draw => 0,
+        }
+    }
+}
+
+type WallKey = ([[bool; 9]; 10], [[bool; 9]; 10]);
+
+fn wall_key(position: &Position) -> WallKey {
+    (position.vertical_walls, position.horizontal_walls)
+}
+
+// Once nobody has a wall in hand, only the pawns move. There are only 81 * 81 * 2 states,
+// so it solves all of them at once with a retrograde analysis, jumps and blockades included.
+pub struct RaceTable {
+    walls: WallKey,
+    outcomes: Vec<Outcome>,
+    successors: Vec<Vec<u16>>,
+}
+
+impl RaceTable {
+    pub fn new(position: &Position) -> Self {
+        let mut successors = vec![vec![]; STATE_COUNT];
+        let mut outcomes = vec![Outcome::Draw; STATE_COUNT];
+        let mut is_solved = vec![false; STATE_COUNT];
+        let mut queue = Vec::with_capacity(STATE_COUNT);
+
+        for state in 0..STATE_COUNT {
+            let (player1, player2, player1_turn) = decode(state);
+
+            if player1 == player2 || player1.0 == 8 && player2.0 == 0 {
+                continue;
+            }
+
+            let (mover, other) = if player1_turn { (player1, player2) } else { (player2, player1) };
+
+            // the other player has just made it
+            if (player1_turn && player2.0 == 0) || (!player1_turn && player1.0 == 8) {
+                outcomes[state] = Outcome::Loss(0);
+                is_solved[state] = true;
+                queue.push(state);
+                continue;
+            }
+
+            // the game is already over, this state is never reached
+            if (player1_turn && player1.0 == 8) || (!player1_turn && player2.0 == 0) {
+                continue;
+            }
+
+            for step in pawn_steps(position, mover, other) {
+                successors[state].push(if player1_turn { encode(step, player2, false) } else { encode(player1, step, true) } as u16);
+            }
+        }
+
+        let mut predecessors = vec![vec![]; STATE_COUNT];
+
+        for (state, nexts) in successors.iter().enumerate() {
+            for next in nexts.iter() {
+                predecessors[*next as usize].push(state as u16);
+            }
+        }
+
+        let mut remaining: Vec<usize> = successors.iter().map(|nexts| nexts.len()).collect();
+        let mut head = 0;
+
+        // breadth first, so that a win takes the shortest way and a loss the longest
+        while head < queue.len() {
+            let state = queue[head];
+            head += 1;
+
+            for prev in predecessors[state].iter() {
+                let prev = *prev as usize;
+
+                if is_solved[prev] {
+                    continue;
+                }
+
+                match outcomes[state] {
+                    Outcome::Loss(plies) => {
+                        outcomes[prev] = Outcome::Win(plies.saturating_add(1));
+                        is_solved[prev] = true;
+                        queue.push(prev);
+                    },
+                    Outcome::Win(plies) => {
+                        remaining[prev] -= 1;
+
+                        if remaining[prev] == 0 {
+                            outcomes[prev] = Outcome::Loss(plies.saturating_add(1));
+                            is_solved[prev] = true;
+                            queue.push(prev);
+                        }
+                    },
+                    Outcome::Draw => {},
+                }
+            }
+        }
+
+        RaceTable {
+            walls: wall_key(position),
+            outcomes,
+            successors,
+        }
+    }
+
+    pub fn is_for(&self, position: &Position) -> bool {
+        self.walls == wall_key(position)
+    }
+
+    // the position must have the same walls as the one that built the table
+    pub fn probe(&self, position: &Position) -> (Outcome, Option<Move>) {
+        let state = encode(position.player1, position.player2, position.player1_turn);
+        let outcome = self.outcomes[state];
+
+        let best = self.successors[state].iter().map(|next| *next as usize).find(
+            |next| match (outcome, self.outcomes[*next]) {
+                (Outcome::Win(plies), Outcome::Loss(next_plies)) => next_plies + 1 == plies,
+                (Outcome::Loss(plies), Outcome::Win(next_plies)) => next_plies + 1 == plies,
+                (Outcome::Draw, next_outcome) => next_outcome == Outcome::Draw,
+                _ => false,
+            }
+        ).or(self.successors[state].first().map(|next| *next as usize));
+
+        let best_move = best.map(|next| {
+            let (player1, player2, _) = decode(next);
+            let (x, y) = if position.player1_turn { player1 } else { player2 };
+            Move::Step(x, y)
+        });
+
+        (outcome, best_move)
+    }
+}
+
+// Solves races exactly, and positions with at most `MAX_WALLS_IN_HAND` walls left
+// with a bounded exhaustive search whose leaves are races.
+pub struct Solver<'a> {
+    races: HashMap<WallKey, RaceTable>,
+    nodes: usize,
+    budget: usize,
+    deadline: Option<Instant>,
+    stop: Option<&'a AtomicBool>,
+}
+
+impl<'a> Solver<'a> {
+    // a new race table costs `RACE_TABLE_COST` nodes
+    pub fn new(budget: usize) -> Self {
+        Solver {
+            races: HashMap::new(),
+            nodes: 0,
+            budget,
+            deadline: None,
+            stop: None,
+        }
+    }
+
+    pub fn with_time_limit(mut self, limit: Duration) -> Self {
+        self.deadline = Some(Instant::now() + limit);
+        self
+    }
+
+    // it gives up as soon as `stop` is set, like the search
+    pub fn with_stop(mut self, stop: &'a AtomicBool) -> Self {
+        self.stop = Some(stop);
+        self
+    }
+
+    fn is_out_of_budget(&self) -> bool {
+        self.nodes > self.budget
+            || self.deadline.map(|deadline| Instant::now() >= deadline).unwrap_or(false)
+            || self.stop.map(|stop| stop.load(Ordering::Relaxed)).unwrap_or(false)
+    }
+
+    // `None` if it could not prove anything within the budget
+    pub fn solve(&mut self, position: &Position) -> Option<(Outcome, Option<Move>)> {
+        if position.walls.0 + position.walls.1 > MAX_WALLS_IN_HAND {
+            return None;
+        }
+
+        for depth in 1..=MAX_SOLVER_DEPTH {
+            match self.prove(position, depth) {
+                Some(result) => { return Some(result); },
+                None if self.is_out_of_budget() => { return None; },
+                None => {},
+            }
+        }
+
+        None
+    }
+
+    fn prove(&mut self, position: &Position, depth: u8) -> Option<(Outcome, Option<Move>)> {
+        self.nodes += 1;
+
+        if self.is_out_of_budget() {
+            return None;
+        }
+
+        if position.winner().is_some() {
+            return Some((Outcome::Loss(0), None));
+        }
+
+        if position.walls == (0, 0) {
+            return Some(self.race(position));
+        }
+
+        if depth == 0 {
+            return None;
+        }
+
+        let mut best_win: Option<(u8, Move)> = None;
+        let mut longest_loss: Option<(u8, Move)> = None;
+        let mut draw = None;
+        let mut is_all_proven = true;
+
+        for mv in position.legal_moves() {
+            let mut next = position.clone();
+            next.apply(mv);
+
+            match self.prove(&next, depth - 1) {
+                Some((Outcome::Loss(plies), _)) => {
+                    if best_win.map(|(best, _)| plies + 1 < best).unwrap_or(true) {
+                        best_win = Some((plies.saturating_add(1), mv));
+                    }
+                },
+                Some((Outcome::Win(plies), _)) => {
+                    if longest_loss.map(|(longest, _)| plies + 1 > longest).unwrap_or(true) {
+                        longest_loss = Some((plies.saturating_add(1), mv));
+                    }
+                },
+                Some((Outcome::Draw, _)) => {
+                    draw = Some(mv);
+                },
+                None => {
+                    if self.is_out_of_budget() {
+                        return None;
+                    }
+
+                    is_all_proven = false;
+                },
+            }
+        }
+
+        if let Some((plies, mv)) = best_win {
+            Some((Outcome::Win(plies), Some(mv)))
+        }
+
+        else if !is_all_proven {
+            None
+        }
+
+        else if let Some(mv) = draw {
+            Some((Outcome::Draw, Some(mv)))
+        }
+
+        else {
+            longest_loss.map(|(plies, mv)| (Outcome::Loss(plies), Some(mv)))
+        }
+    }
+
+    pub fn race(&mut self, position: &Position) -> (Outcome, Option<Move>) {
+        let key = wall_key(position);
+
+        if !self.races.contains_key(&key) {
+            self.nodes += RACE_TABLE_COST;
+            self.races.insert(key, RaceTable::new(position));
+        }
+
+        self.races[&key].probe(position)
+    }
+}
+
+// the same rule as `Position::valid_steps`, for any pair of pawns
+fn pawn_steps(position: &Position, mover: (i32, i32), other: (i32, i32)) -> Vec<(i32, i32)> {
+    let mut result = Vec::with_capacity(4);
+
+    for (dx, dy) in [(1, 0), (0, 1), (-1, 0), (0, -1)] {
+        let next = (mover.0 + dx, mover.1 + dy);
+
+        if !is_on_board(next) || !position.is_movable_at(mover, (dx, dy)) {
+            continue;
+        }
+
+        if next != other {
+            result.push(next);
+            continue;
+        }
+
+        let jump = (next.0 + dx, next.1 + dy);
+
+        if is_on_board(jump) && position.is_movable_at(next, (dx, dy)) {
+            result.push(jump);
+        }
+    }
+
+    result
+}
+
+fn is_on_board((x, y): (i32, i32)) -> bool {
+    (0..9).contains(&x) && (0..9).contains(&y)
+}
+
+fn encode(player1: (i32, i32), player2: (i32, i32), player1_turn: bool) -> usize {
+    (((player1.0 * 9 + player1.1) * 81 + player2.0 * 9 + player2.1) * 2) as usize + if player1_turn { 0 } else { 1 }
+}
+
+fn decode(state: usize) -> ((i32, i32), (i32, i32), bool) {
+    let (cells, turn) = (state / 2, state % 2);
+    let (cell1, cell2) = ((cells / 81) as i32, (cells % 81) as i32);
+
+    ((cell1 / 9, cell1 % 9), (cell2 / 9, cell2 % 9), turn == 0)
+}
+
+const STATE_COUNT: usize = 81 * 81 * 2;
+const RACE_TABLE_COST: usize = 500;
+const MAX_WALLS_IN_HAND: usize = 2;
+const MAX_SOLVER_DEPTH: u8 = 6;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a race without walls, player 1 at `player1` and player 2 at `player2`
+    fn race(player1: (i32, i32), player2: (i32, i32), player1_turn: bool, walls: usize) -> Position {
+        let mut position = Position::new();
+        position.player1 = player1;
+        position.player2 = player2;
+        position.walls = (walls, walls);
+        position.player1_turn = player1_turn;
+
+        position
+    }
+
+    #[test]
+    fn moves_to_end() {
+        assert_eq!(Outcome::Win(1).moves_to_end(), 1);
+        assert_eq!(Outcome::Win(3).moves_to_end(), 2);
+        assert_eq!(Outcome::Loss(4).moves_to_end(), 2);
+        assert_eq!(Outcome::Draw.moves_to_end(), 0);
+    }
+
+    #[test]
+    fn race_table_finds_the_winning_step() {
+        let position = race((7, 0), (1, 8), true, 0);
+        let table = RaceTable::new(&position);
+
+        assert!(table.is_for(&position));
+        assert_eq!(table.probe(&position), (Outcome::Win(1), Some(Move::Step(8, 0))));
+
+        // the other pawn is one step ahead
+        assert_eq!(table.probe(&race((7, 0), (1, 8), false, 0)).0, Outcome::Win(1));
+        assert_eq!(table.probe(&race((6, 0), (1, 8), true, 0)).0, Outcome::Loss(2));
+    }
+
+    #[test]
+    fn race_table_agrees_with_the_distances() {
+        let position = race((2, 1), (5, 7), true, 0);
+        let table = RaceTable::new(&position);
+
+        // far apart, so the one who needs fewer moves wins, player 1 on a tie because it moves first
+        for player1 in [(2, 1), (4, 0), (5, 2)] {
+            for player2 in [(5, 7), (3, 8), (1, 6)] {
+                let position = race(player1, player2, true, 0);
+                let mine = position.distance(true).unwrap();
+                let theirs = position.distance(false).unwrap();
+
+                match table.probe(&position).0 {
+                    Outcome::Win(plies) => {
+                        assert!(mine <= theirs);
+                        assert_eq!(plies, mine * 2 - 1);
+                    },
+                    Outcome::Loss(plies) => {
+                        assert!(mine > theirs);
+                        assert_eq!(plies, theirs * 2);
+                    },
+                    Outcome::Draw => panic!("a race always ends"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn solver_matches_the_race_table() {
+        let position = race((5, 2), (3, 6), false, 0);
+
+        assert_eq!(Solver::new(1000).solve(&position), Some(RaceTable::new(&position).probe(&position)));
+    }
+
+    #[test]
+    fn solver_with_walls_in_hand() {
+        // player 2 is one step from the goal, and the walls don't matter
+        let position = race((1, 0), (1, 8), false, 1);
+
+        assert_eq!(Solver::new(200_000).solve(&position), Some((Outcome::Win(1), Some(Move::Step(0, 8)))));
+    }
+
+    #[test]
+    fn solver_gives_up() {
+        // too many walls in hand
+        assert_eq!(Solver::new(1_000_000).solve(&Position::new()), None);
+
+        let stop = AtomicBool::new(true);
+        assert_eq!(Solver::new(1_000_000).with_stop(&stop).solve(&race((4, 0), (4, 8), true, 2)), None);
+    }
+}
@@ -0,0 +1,123 @@
+use crate::engine::file_io::{read_string, write_to_file};
+use crate::position::Position;
+
+pub const MATE: i32 = 30000;
+
+// scores above this are wins or losses found by the search
+pub const MATE_BOUND: i32 = MATE - 1000;
+
+// The evaluation is a weighted sum of these features, each from the view of the player to move.
+// `tune` fits the weights and writes them to `PARAMS_PATH`, and the cpu loads that file at startup.
+pub const FEATURE_NAMES: [&str; FEATURE_COUNT] = [
+    "path_difference",  // the opponent's shortest path minus mine
+    "walls_in_hand",    // my walls minus the opponent's
+    "mobility",         // my pawn moves minus the opponent's
+    "path_proximity",   // how much closer my pawn is to the opponent's path than the opponent's pawn is to mine
+    "tempo",            // always 1
+];
+pub const FEATURE_COUNT: usize = 5;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Weights {
+    pub values: [i32; FEATURE_COUNT],
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Weights {
+            values: [100, 30, 0, 0, 50],
+        }
+    }
+}
+
+impl Weights {
+    // missing features keep their default weights
+    pub fn load(path: &str) -> Result<Self, String> {
+        let s = read_string(path).map_err(|_| format!("Failed to open file: {}", path))?;
+        let mut weights = Weights::default();
+
+        for line in s.lines() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (name, value) = match line.split_once(char::is_whitespace) {
+                Some((name, value)) => (name.trim(), value.trim()),
+                None => { return Err(format!("{}: invalid line in a parameter file: `{}`", path, line)); },
+            };
+
+            match (FEATURE_NAMES.iter().position(|feature| *feature == name), value.parse()) {
+                (Some(index), Ok(value)) => { weights.values[index] = value; },
+                _ => { return Err(format!("{}: invalid line in a parameter file: `{}`", path, line)); },
+            }
+        }
+
+        Ok(weights)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let mut lines = vec!["# Quoridor evaluation weights".to_string()];
+
+        for (name, value) in FEATURE_NAMES.iter().zip(self.values.iter()) {
+            lines.push(format!("{} {}", name, value));
+        }
+
+        write_to_file(path, (lines.join("\n") + "\n").as_bytes()).map_err(|_| format!("Failed to write file: {}", path))
+    }
+}
+
+// from the view of the player to move
+pub fn evaluate(position: &Position, weights: &Weights) -> i32 {
+    match features(position, weights) {
+        Some(features) => features.iter().zip(weights.values.iter()).map(|(feature, weight)| feature * weight).sum(),
+
+        // unreachable with legal moves
+        None => 0,
+    }
+}
+
+// The features whose weights are 0 are not calculated, because the search calls this a lot.
+// Pass `None` to get all of them.
+pub fn features<'a>(position: &Position, weights: impl Into<Option<&'a Weights>>) -> Option<[i32; FEATURE_COUNT]> {
+    let weights = weights.into();
+    let is_used = |index: usize| weights.map(|weights| weights.values[index] != 0).unwrap_or(true);
+    let me = position.player1_turn;
+    let mut result = [0; FEATURE_COUNT];
+
+    if is_used(PATH_PROXIMITY) {
+        let (my_path, opponent_path) = (position.shortest_path(me)?, position.shortest_path(!me)?);
+        result[PATH_DIFFERENCE] = opponent_path.len() as i32 - my_path.len() as i32;
+
+        let (my_pawn, opponent_pawn) = if me { (position.player1, position.player2) } else { (position.player2, position.player1) };
+        result[PATH_PROXIMITY] = distance_to_path(opponent_pawn, &my_path) - distance_to_path(my_pawn, &opponent_path);
+    }
+
+    else {
+        result[PATH_DIFFERENCE] = position.distance(!me)? as i32 - position.distance(me)? as i32;
+    }
+
+    let (walls1, walls2) = (position.walls.0 as i32, position.walls.1 as i32);
+    result[WALLS_IN_HAND] = if me { walls1 - walls2 } else { walls2 - walls1 };
+
+    if is_used(MOBILITY) {
+        result[MOBILITY] = position.steps_of(me).len() as i32 - position.steps_of(!me).len() as i32;
+    }
+
+    result[TEMPO] = 1;
+
+    Some(result)
+}
+
+fn distance_to_path(pawn: (i32, i32), path: &[(i32, i32)]) -> i32 {
+    path.iter().map(|(x, y)| (x - pawn.0).abs() + (y - pawn.1).abs()).min().unwrap_or(0)
+}
+
+const PATH_DIFFERENCE: usize = 0;
+const WALLS_IN_HAND: usize = 1;
+const MOBILITY: usize = 2;
+const PATH_PROXIMITY: usize = 3;
+const TEMPO: usize = 4;
+
+pub const PARAMS_PATH: &str = "params.txt";
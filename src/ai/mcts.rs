@@ -0,0 +1,356 @@
+use crate::position::{Move, Position};
+use macroquad::rand::gen_range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+// How the playouts pick their moves
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RolloutPolicy {
+    Random,
+    GreedyPath,                // always a step along the shortest path, never a wall
+    EpsilonGreedyWalls(f32),   // like `GreedyPath`, but places the most annoying wall with the given probability
+}
+
+impl RolloutPolicy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "random" => Some(RolloutPolicy::Random),
+            "greedy" => Some(RolloutPolicy::GreedyPath),
+            "epsilon" => Some(RolloutPolicy::EpsilonGreedyWalls(0.2)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MctsConfig {
+    pub playouts: Option<usize>,
+    pub time_limit: Option<Duration>,
+    pub policy: RolloutPolicy,
+    pub exploration: f64,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        MctsConfig {
+            playouts: Some(2000),
+            time_limit: None,
+            policy: RolloutPolicy::EpsilonGreedyWalls(0.2),
+            exploration: 1.4,
+        }
+    }
+}
+
+struct Node {
+    mv: Option<Move>,  // the move that leads to this node, `None` for the root
+    children: Vec<usize>,
+    untried: Vec<Move>,  // the most promising one last, because it's popped first
+    is_expanded: bool,
+    visits: u32,
+    wins: f64,  // for the player who played `mv`
+}
+
+// Monte Carlo tree search with UCT. Alpha-beta has to look at every wall slot on every node,
+// but here a wall only gets playouts if the tree keeps choosing it.
+// The tree survives between the moves: if the next position is a child or a grandchild of the last root,
+// that subtree becomes the new root.
+pub struct Mcts {
+    config: MctsConfig,
+    nodes: Vec<Node>,
+    root_position: Option<Position>,
+}
+
+impl Mcts {
+    pub fn new(config: MctsConfig) -> Self {
+        Mcts {
+            config,
+            nodes: vec![],
+            root_position: None,
+        }
+    }
+
+    // it stops at whichever comes first: `config.playouts`, `config.time_limit`, `deadline` or `stop`
+    pub fn search(&mut self, position: &Position, deadline: Option<Instant>, stop: &AtomicBool) -> Option<Move> {
+        self.reuse_tree(position);
+
+        if self.nodes.is_empty() {
+            self.nodes.push(Node::new(None));
+        }
+
+        let started_at = Instant::now();
+        let deadline = match (self.config.time_limit.map(|limit| started_at + limit), deadline) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        let mut playouts = 0;
+
+        loop {
+            if self.config.playouts.map(|limit| playouts >= limit).unwrap_or(false)
+                || deadline.map(|deadline| Instant::now() >= deadline).unwrap_or(false)
+                || (self.config.playouts.is_none() && deadline.is_none() && playouts >= DEFAULT_PLAYOUTS)
+                || (playouts & 63 == 63 && stop.load(Ordering::Relaxed)) {
+                break;
+            }
+
+            self.playout(position);
+            playouts += 1;
+        }
+
+        self.nodes[0].children.iter().max_by_key(|child| self.nodes[**child].visits).and_then(|child| self.nodes[*child].mv)
+    }
+
+    fn playout(&mut self, root_position: &Position) {
+        let mut position = root_position.clone();
+        let mut path = vec![0];
+        let mut curr = 0;
+
+        // selection
+        while self.nodes[curr].is_expanded && self.nodes[curr].untried.is_empty() && !self.nodes[curr].children.is_empty() {
+            curr = self.select_child(curr);
+            position.apply(self.nodes[curr].mv.unwrap());
+            path.push(curr);
+        }
+
+        // expansion
+        if position.winner().is_none() {
+            if !self.nodes[curr].is_expanded {
+                self.nodes[curr].untried = ordered_moves(&position);
+                self.nodes[curr].is_expanded = true;
+            }
+
+            if let Some(mv) = self.nodes[curr].untried.pop() {
+                let child = self.nodes.len();
+                self.nodes.push(Node::new(Some(mv)));
+                self.nodes[curr].children.push(child);
+                position.apply(mv);
+                path.push(child);
+            }
+        }
+
+        let player1_won = rollout(&mut position, self.config.policy);
+
+        // backpropagation: the player who made the move into the root is the one who is not to move
+        let mut player1_moved = !root_position.player1_turn;
+
+        for node in path {
+            self.nodes[node].visits += 1;
+
+            if player1_moved == player1_won {
+                self.nodes[node].wins += 1.0;
+            }
+
+            player1_moved = !player1_moved;
+        }
+    }
+
+    fn select_child(&self, parent: usize) -> usize {
+        let log_visits = (self.nodes[parent].visits.max(1) as f64).ln();
+
+        *self.nodes[parent].children.iter().max_by(|a, b| {
+            let (a, b) = (self.uct(**a, log_visits), self.uct(**b, log_visits));
+            a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+        }).unwrap()
+    }
+
+    fn uct(&self, node: usize, log_parent_visits: f64) -> f64 {
+        let node = &self.nodes[node];
+        let visits = node.visits.max(1) as f64;
+
+        node.wins / visits + self.config.exploration * (log_parent_visits / visits).sqrt()
+    }
+
+    fn reuse_tree(&mut self, position: &Position) {
+        let new_root = match &self.root_position {
+            Some(old_root) if !self.nodes.is_empty() => self.find_descendant(old_root, position),
+            _ => None,
+        };
+
+        self.nodes = match new_root {
+            Some(new_root) => self.extract_subtree(new_root),
+            None => vec![],
+        };
+
+        self.root_position = Some(position.clone());
+    }
+
+    // a child or a grandchild of the root, whose position is `target`
+    fn find_descendant(&self, root_position: &Position, target: &Position) -> Option<usize> {
+        if root_position.hash() == target.hash() {
+            return Some(0);
+        }
+
+        for child in self.nodes[0].children.iter() {
+            let mut child_position = root_position.clone();
+            child_position.apply(self.nodes[*child].mv?);
+
+            if child_position.hash() == target.hash() {
+                return Some(*child);
+            }
+
+            for grandchild in self.nodes[*child].children.iter() {
+                let mut grandchild_position = child_position.clone();
+                grandchild_position.apply(self.nodes[*grandchild].mv?);
+
+                if grandchild_position.hash() == target.hash() {
+                    return Some(*grandchild);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn extract_subtree(&mut self, new_root: usize) -> Vec<Node> {
+        let mut result = vec![];
+        let mut stack = vec![(new_root, None)];
+
+        while let Some((old_index, parent)) = stack.pop() {
+            let new_index = result.len();
+            let old = &mut self.nodes[old_index];
+
+            result.push(Node {
+                mv: if parent.is_none() { None } else { old.mv },
+                children: vec![],
+                untried: std::mem::take(&mut old.untried),
+                is_expanded: old.is_expanded,
+                visits: old.visits,
+                wins: old.wins,
+            });
+
+            if let Some(parent) = parent {
+                let parent: &mut Node = &mut result[parent];
+                parent.children.push(new_index);
+            }
+
+            for child in old.children.iter() {
+                stack.push((*child, Some(new_index)));
+            }
+        }
+
+        result
+    }
+}
+
+impl Node {
+    fn new(mv: Option<Move>) -> Self {
+        Node {
+            mv,
+            children: vec![],
+            untried: vec![],
+            is_expanded: false,
+            visits: 0,
+            wins: 0.0,
+        }
+    }
+}
+
+// walls that hurt the opponent more than me are tried first, then the steps that shorten my path
+fn ordered_moves(position: &Position) -> Vec<Move> {
+    let me = position.player1_turn;
+    let mut scored = vec![];
+
+    for (x, y) in position.valid_steps() {
+        let mut next = position.clone();
+        next.apply(Move::Step(x, y));
+        scored.push((-(next.distance(me).unwrap_or(u8::MAX) as i32) * 2, Move::Step(x, y)));
+    }
+
+    if position.curr_walls() > 0 {
+        let (distance1, distance2) = (position.distance(true).unwrap_or(0) as i32, position.distance(false).unwrap_or(0) as i32);
+
+        for (mv, d1, d2) in position.wall_candidates(true) {
+            let gain = if me { (d2 as i32 - distance2) - (d1 as i32 - distance1) } else { (d1 as i32 - distance1) - (d2 as i32 - distance2) };
+
+            // the walls that do nothing are left out, or the tree would be too wide
+            if gain > 0 {
+                scored.push((gain * 3 - 20, mv));
+            }
+        }
+    }
+
+    scored.sort_by_key(|(score, _)| *score);
+    scored.into_iter().map(|(_, mv)| mv).collect()
+}
+
+// it returns whether player 1 won
+fn rollout(position: &mut Position, policy: RolloutPolicy) -> bool {
+    for _ in 0..MAX_ROLLOUT_PLIES {
+        if let Some(winner) = position.winner() {
+            return winner;
+        }
+
+        match rollout_move(position, policy) {
+            Some(mv) => { position.apply(mv); },
+            None => { break; },
+        }
+    }
+
+    if let Some(winner) = position.winner() {
+        return winner;
+    }
+
+    // too long: the shorter path wins, and the player to move wins a tie
+    let (distance1, distance2) = (position.distance(true).unwrap_or(u8::MAX), position.distance(false).unwrap_or(u8::MAX));
+
+    distance1 < distance2 || distance1 == distance2 && position.player1_turn
+}
+
+fn rollout_move(position: &Position, policy: RolloutPolicy) -> Option<Move> {
+    match policy {
+        RolloutPolicy::Random => {
+            if position.curr_walls() > 0 && gen_range(0.0, 1.0) < RANDOM_WALL_PROBABILITY {
+                // a few tries, since a random wall is often not allowed
+                for _ in 0..8 {
+                    let mv = if gen_range(0, 2) == 0 {
+                        Move::Vertical(gen_range(1, 9), gen_range(0, 8))
+                    } else {
+                        Move::Horizontal(gen_range(0, 8), gen_range(1, 9))
+                    };
+
+                    if position.is_legal(mv) {
+                        return Some(mv);
+                    }
+                }
+            }
+
+            let steps = position.valid_steps();
+            steps.get(gen_range(0, steps.len().max(1))).map(|(x, y)| Move::Step(*x, *y))
+        },
+        RolloutPolicy::GreedyPath => greedy_step(position),
+        RolloutPolicy::EpsilonGreedyWalls(epsilon) => {
+            if position.curr_walls() > 0 && gen_range(0.0, 1.0) < epsilon {
+                if let Some(mv) = best_wall(position) {
+                    return Some(mv);
+                }
+            }
+
+            greedy_step(position)
+        },
+    }
+}
+
+fn greedy_step(position: &Position) -> Option<Move> {
+    let me = position.player1_turn;
+
+    position.valid_steps().into_iter().map(|(x, y)| {
+        let mut next = position.clone();
+        next.apply(Move::Step(x, y));
+        (next.distance(me).unwrap_or(u8::MAX), Move::Step(x, y))
+    }).min_by_key(|(distance, _)| *distance).map(|(_, mv)| mv)
+}
+
+// the wall that lengthens the opponent's path the most, if it lengthens it at all
+fn best_wall(position: &Position) -> Option<Move> {
+    let me = position.player1_turn;
+
+    position.wall_candidates(true).into_iter().map(
+        |(mv, d1, d2)| if me { (d2 as i32 - d1 as i32, mv) } else { (d1 as i32 - d2 as i32, mv) }
+    ).max_by_key(|(gain, _)| *gain).filter(|(gain, _)| {
+        let (distance1, distance2) = (position.distance(true).unwrap_or(0) as i32, position.distance(false).unwrap_or(0) as i32);
+        *gain > if me { distance2 - distance1 } else { distance1 - distance2 }
+    }).map(|(_, mv)| mv)
+}
+
+const MAX_ROLLOUT_PLIES: usize = 200;
+const DEFAULT_PLAYOUTS: usize = 2000;
+const RANDOM_WALL_PROBABILITY: f32 = 0.25;
@@ -0,0 +1,65 @@
+use super::eval::{Weights, FEATURE_NAMES};
+
+// Styles of the alpha-beta cpu. They don't make it stronger or weaker on purpose:
+// they bend its evaluation, so that it prefers some kinds of moves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Personality {
+    Balanced,
+    Waller,     // spends its walls early to lengthen the opponent's path
+    Racer,      // hoards its walls and runs
+    Defensive,  // stays close to the opponent's path, with walls in hand to answer the threats
+}
+
+impl Personality {
+    pub const ALL: [Personality; 4] = [Personality::Balanced, Personality::Waller, Personality::Racer, Personality::Defensive];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Personality::Balanced => "balanced",
+            Personality::Waller => "waller",
+            Personality::Racer => "racer",
+            Personality::Defensive => "defensive",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Personality::ALL.into_iter().find(|personality| personality.name() == name)
+    }
+
+    pub fn next(self) -> Self {
+        let index = Personality::ALL.iter().position(|personality| *personality == self).unwrap_or(0);
+        Personality::ALL[(index + 1) % Personality::ALL.len()]
+    }
+
+    pub fn apply(self, weights: &Weights) -> Weights {
+        let mut result = weights.clone();
+        let walls_in_hand = feature_index("walls_in_hand");
+        let path_difference = feature_index("path_difference");
+        let path_proximity = feature_index("path_proximity");
+
+        match self {
+            Personality::Balanced => {},
+
+            // a wall in hand is worth little, so any wall that hurts the opponent is worth placing
+            Personality::Waller => {
+                result.values[walls_in_hand] /= 4;
+                result.values[path_difference] = result.values[path_difference] * 5 / 4;
+            },
+            Personality::Racer => {
+                result.values[walls_in_hand] *= 3;
+            },
+            Personality::Defensive => {
+                result.values[walls_in_hand] *= 2;
+                result.values[path_proximity] = result.values[path_proximity].max(DEFENSIVE_PROXIMITY_WEIGHT);
+            },
+        }
+
+        result
+    }
+}
+
+fn feature_index(name: &str) -> usize {
+    FEATURE_NAMES.iter().position(|feature| *feature == name).unwrap()
+}
+
+const DEFENSIVE_PROXIMITY_WEIGHT: i32 = 20;
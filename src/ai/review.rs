@@ -0,0 +1,135 @@
+use super::eval::{Weights, MATE};
+use super::search::{search, SearchConfig};
+use super::tt::TranspositionTable;
+use super::TT_BITS;
+use crate::position::{Move, Position};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// How much a move dropped the evaluation, compared to the best move at the same depth.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Annotation {
+    Best,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl Annotation {
+    fn from_loss(loss: i32) -> Self {
+        if loss >= BLUNDER_LOSS {
+            Annotation::Blunder
+        }
+
+        else if loss >= MISTAKE_LOSS {
+            Annotation::Mistake
+        }
+
+        else if loss >= INACCURACY_LOSS {
+            Annotation::Inaccuracy
+        }
+
+        else {
+            Annotation::Best
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Annotation::Best => "best",
+            Annotation::Inaccuracy => "inaccuracy",
+            Annotation::Mistake => "mistake",
+            Annotation::Blunder => "blunder",
+        }
+    }
+
+    // the chess symbols, put after the move in the move list and the game records
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Annotation::Best => "",
+            Annotation::Inaccuracy => "?!",
+            Annotation::Mistake => "?",
+            Annotation::Blunder => "??",
+        }
+    }
+
+    pub fn from_symbol(s: &str) -> Option<Self> {
+        [Annotation::Best, Annotation::Inaccuracy, Annotation::Mistake, Annotation::Blunder].into_iter().find(|annotation| annotation.symbol() == s)
+    }
+}
+
+pub struct Review {
+    pub annotations: Vec<Annotation>,  // one per move
+    pub best_moves: Vec<Option<Move>>,
+    pub scores: Vec<i32>,  // player 1's view, of the position before each move and after the last one
+}
+
+// Searches every position of the game at `depth`. A move that is not the best one is searched
+// one ply shallower from the opponent's view, which is how the best move was scored, and loses
+// the difference. Wins and losses are capped at `SCORE_CAP`, so that missing a faster win is not a blunder.
+// `None` if it's stopped or a move is illegal.
+pub fn review(start: &Position, moves: &[Move], depth: u8, weights: &Weights, stop: &AtomicBool) -> Option<Review> {
+    let tt = TranspositionTable::new(TT_BITS);
+    let config = SearchConfig {
+        threads: super::thread_count(),
+        max_depth: depth,
+        weights: weights.clone(),
+        ..SearchConfig::default()
+    };
+    let child_config = SearchConfig {
+        max_depth: depth.saturating_sub(1).max(1),
+        ..config.clone()
+    };
+
+    let mut position = start.clone();
+    let mut annotations = Vec::with_capacity(moves.len());
+    let mut best_moves = Vec::with_capacity(moves.len());
+    let mut scores = Vec::with_capacity(moves.len() + 1);
+
+    for mv in moves.iter() {
+        if !position.is_legal(*mv) {
+            return None;
+        }
+
+        let result = search(&position, &config, &tt, stop);
+        let best = result.score.clamp(-SCORE_CAP, SCORE_CAP);
+        scores.push(if position.player1_turn { best } else { -best });
+        best_moves.push(result.best_move);
+
+        let mut next = position.clone();
+        next.apply(*mv);
+
+        let annotation = if result.best_move == Some(*mv) {
+            Annotation::Best
+        } else {
+            let score = match next.winner() {
+                Some(_) => MATE,
+                None => -search(&next, &child_config, &tt, stop).score,
+            };
+
+            Annotation::from_loss(best - score.clamp(-SCORE_CAP, SCORE_CAP))
+        };
+
+        if stop.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        annotations.push(annotation);
+        position = next;
+    }
+
+    // the game may be over, and there's nothing to search then
+    let last_score = match position.winner() {
+        Some(_) => -SCORE_CAP,
+        None => search(&position, &config, &tt, stop).score.clamp(-SCORE_CAP, SCORE_CAP),
+    };
+    scores.push(if position.player1_turn { last_score } else { -last_score });
+
+    Some(Review { annotations, best_moves, scores })
+}
+
+const INACCURACY_LOSS: i32 = 50;
+const MISTAKE_LOSS: i32 = 100;
+const BLUNDER_LOSS: i32 = 200;
+const SCORE_CAP: i32 = 1000;
+
+pub const REVIEW_DEPTH: u8 = 3;
@@ -0,0 +1,376 @@
+use super::endgame::{Outcome, RaceTable, Solver};
+use super::eval::{evaluate, Weights, MATE, MATE_BOUND};
+use super::tt::{Bound, Entry, TranspositionTable};
+use crate::position::{Move, Position};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Clone)]
+pub struct SearchConfig {
+    pub threads: usize,
+    pub max_depth: u8,
+    pub time_limit: Option<Duration>,
+    pub weights: Weights,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        SearchConfig {
+            threads: 1,
+            max_depth: MAX_DEPTH,
+            time_limit: None,
+            weights: Weights::default(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    pub score: i32,  // from the view of the player to move
+    pub depth: u8,
+    pub nodes: u64,
+    pub elapsed: Duration,
+    pub pv: Vec<Move>,
+}
+
+// Lazy SMP: every thread runs its own iterative deepening on the same position,
+// and they only talk to each other through `tt`. The result of thread 0 is returned.
+// With `config.threads == 1`, a fresh `tt` and no `time_limit`, the result is reproducible.
+pub fn search(position: &Position, config: &SearchConfig, tt: &TranspositionTable, stop: &AtomicBool) -> SearchResult {
+    let started_at = Instant::now();
+
+    // it does not have to search if the endgame solver can prove the result
+    let mut solver = Solver::new(SOLVER_BUDGET).with_stop(stop);
+
+    if let Some(limit) = config.time_limit {
+        solver = solver.with_time_limit(limit / 4);
+    }
+
+    if let Some((outcome, Some(mv))) = solver.solve(position) {
+        if outcome != Outcome::Draw {
+            return SearchResult {
+                best_move: Some(mv),
+                score: outcome_score(outcome, 0),
+                depth: 0,
+                nodes: 0,
+                elapsed: Instant::now().duration_since(started_at),
+                pv: vec![mv],
+            };
+        }
+    }
+
+    let deadline = config.time_limit.map(|limit| started_at + limit);
+    let nodes = AtomicU64::new(0);
+    let helpers_stop = AtomicBool::new(false);
+
+    let (best_move, score, depth) = thread::scope(|scope| {
+        for id in 1..config.threads.max(1) {
+            let (nodes, helpers_stop) = (&nodes, &helpers_stop);
+
+            scope.spawn(move || {
+                let mut worker = Worker::new(tt, &config.weights, [stop, helpers_stop], deadline, id);
+                worker.iterate(position, config.max_depth);
+                nodes.fetch_add(worker.nodes, Ordering::Relaxed);
+            });
+        }
+
+        let mut worker = Worker::new(tt, &config.weights, [stop, &helpers_stop], deadline, 0);
+        let result = worker.iterate(position, config.max_depth);
+        helpers_stop.store(true, Ordering::Relaxed);
+        nodes.fetch_add(worker.nodes, Ordering::Relaxed);
+
+        result
+    });
+
+    // it's stopped before finishing even the first iteration
+    let best_move = best_move.or_else(|| position.legal_moves().first().copied());
+
+    SearchResult {
+        best_move,
+        score,
+        depth,
+        nodes: nodes.load(Ordering::Relaxed),
+        elapsed: Instant::now().duration_since(started_at),
+        pv: match best_move {
+            Some(mv) => principal_variation(position, tt, mv, depth as usize),
+            None => vec![],
+        },
+    }
+}
+
+struct Worker<'a> {
+    tt: &'a TranspositionTable,
+    weights: &'a Weights,
+    stops: [&'a AtomicBool; 2],
+    deadline: Option<Instant>,
+    id: usize,
+    nodes: u64,
+    aborted: bool,
+    race: Option<RaceTable>,  // once the walls run out, they never change again
+}
+
+impl<'a> Worker<'a> {
+    fn new(tt: &'a TranspositionTable, weights: &'a Weights, stops: [&'a AtomicBool; 2], deadline: Option<Instant>, id: usize) -> Self {
+        Worker {
+            tt, weights, stops, deadline, id,
+            nodes: 0,
+            aborted: false,
+            race: None,
+        }
+    }
+
+    fn iterate(&mut self, position: &Position, max_depth: u8) -> (Option<Move>, i32, u8) {
+        let mut result = (None, 0, 0);
+        let mut depth = 1;
+
+        while depth <= max_depth {
+            // half of the helpers run one ply ahead, so that the threads do not search in lockstep
+            let curr_depth = if self.id % 2 == 1 { (depth + 1).min(max_depth) } else { depth };
+            let (best_move, score) = self.root(position, curr_depth as i32);
+
+            if self.aborted {
+                break;
+            }
+
+            result = (best_move, score, curr_depth);
+
+            if score.abs() > MATE_BOUND {
+                break;
+            }
+
+            depth = curr_depth + 1;
+        }
+
+        result
+    }
+
+    fn root(&mut self, position: &Position, depth: i32) -> (Option<Move>, i32) {
+        let tt_move = self.tt.probe(position.hash()).and_then(|entry| entry.best_move);
+        let moves = self.ordered_moves(position, 0, tt_move);
+        let (mut alpha, beta) = (-MATE - 1, MATE + 1);
+        let mut best_move = None;
+
+        for (index, mv) in moves.into_iter().enumerate() {
+            let score = self.search_child(position, mv, depth, alpha, beta, 0, index == 0);
+
+            if self.aborted {
+                return (best_move, alpha);
+            }
+
+            if score > alpha {
+                alpha = score;
+                best_move = Some(mv);
+            }
+        }
+
+        self.tt.store(position.hash(), Entry {
+            best_move,
+            score: alpha,
+            depth: depth as u8,
+            bound: Bound::Exact,
+        });
+
+        (best_move, alpha)
+    }
+
+    // principal variation search: a null window first, and a full window only if it fails high
+    #[allow(clippy::too_many_arguments)]
+    fn search_child(&mut self, position: &Position, mv: Move, depth: i32, alpha: i32, beta: i32, ply: i32, is_first: bool) -> i32 {
+        let mut next = position.clone();
+        next.apply(mv);
+
+        if is_first {
+            return -self.negamax(&next, depth - 1, -beta, -alpha, ply + 1);
+        }
+
+        let score = -self.negamax(&next, depth - 1, -alpha - 1, -alpha, ply + 1);
+
+        if score > alpha && score < beta {
+            -self.negamax(&next, depth - 1, -beta, -alpha, ply + 1)
+        }
+
+        else {
+            score
+        }
+    }
+
+    fn negamax(&mut self, position: &Position, depth: i32, mut alpha: i32, beta: i32, ply: i32) -> i32 {
+        self.nodes += 1;
+
+        if self.nodes & 1023 == 0 && self.should_stop() {
+            self.aborted = true;
+        }
+
+        if self.aborted {
+            return 0;
+        }
+
+        // only the player who just moved can have won
+        if position.winner().is_some() {
+            return -(MATE - ply);
+        }
+
+        if position.walls == (0, 0) {
+            if !self.race.as_ref().map(|race| race.is_for(position)).unwrap_or(false) {
+                self.race = Some(RaceTable::new(position));
+            }
+
+            if let Some(race) = &self.race {
+                return outcome_score(race.probe(position).0, ply);
+            }
+        }
+
+        if depth <= 0 {
+            return evaluate(position, self.weights);
+        }
+
+        let hash = position.hash();
+        let mut tt_move = None;
+
+        if let Some(entry) = self.tt.probe(hash) {
+            tt_move = entry.best_move;
+
+            if entry.depth as i32 >= depth {
+                let score = score_from_tt(entry.score, ply);
+
+                match entry.bound {
+                    Bound::Exact => { return score; },
+                    Bound::Lower if score >= beta => { return score; },
+                    Bound::Upper if score <= alpha => { return score; },
+                    _ => {},
+                }
+            }
+        }
+
+        let moves = self.ordered_moves(position, ply, tt_move);
+
+        if moves.is_empty() {
+            return evaluate(position, self.weights);
+        }
+
+        let original_alpha = alpha;
+        let mut best_score = -MATE - 1;
+        let mut best_move = None;
+
+        for (index, mv) in moves.into_iter().enumerate() {
+            let score = self.search_child(position, mv, depth, alpha, beta, ply, index == 0);
+
+            if self.aborted {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_move = Some(mv);
+            }
+
+            alpha = alpha.max(score);
+
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+
+        self.tt.store(hash, Entry {
+            best_move,
+            score: score_to_tt(best_score, ply),
+            depth: depth as u8,
+            bound,
+        });
+
+        best_score
+    }
+
+    // the root looks at every wall, the other nodes only at the walls that touch a shortest path
+    fn ordered_moves(&self, position: &Position, ply: i32, tt_move: Option<Move>) -> Vec<Move> {
+        let player1 = position.player1_turn;
+        let mut scored = Vec::with_capacity(64);
+
+        for (x, y) in position.valid_steps() {
+            let mut next = position.clone();
+            next.apply(Move::Step(x, y));
+            let distance = next.distance(player1).unwrap_or(u8::MAX) as i32;
+            scored.push((20000 - distance * 100, Move::Step(x, y)));
+        }
+
+        if position.curr_walls() > 0 {
+            let (distance1, distance2) = (
+                position.distance(true).unwrap_or(0) as i32,
+                position.distance(false).unwrap_or(0) as i32,
+            );
+
+            for (mv, d1, d2) in position.wall_candidates(ply > 0) {
+                let (gain1, gain2) = (d1 as i32 - distance1, d2 as i32 - distance2);
+                let score = if player1 { gain2 - gain1 } else { gain1 - gain2 };
+                scored.push((10000 + score * 100, mv));
+            }
+        }
+
+        for (score, mv) in scored.iter_mut() {
+            if Some(*mv) == tt_move {
+                *score = i32::MAX;
+            }
+
+            // helpers break the ties differently so that they explore different subtrees first
+            else if self.id > 0 {
+                *score += (mv.to_index() as usize * 31 + self.id * 17) as i32 % 7;
+            }
+        }
+
+        scored.sort_by(|(a, _), (b, _)| b.cmp(a));
+        scored.into_iter().map(|(_, mv)| mv).collect()
+    }
+
+    fn should_stop(&self) -> bool {
+        self.stops.iter().any(|stop| stop.load(Ordering::Relaxed))
+        || self.deadline.map(|deadline| Instant::now() >= deadline).unwrap_or(false)
+    }
+}
+
+fn principal_variation(position: &Position, tt: &TranspositionTable, first_move: Move, max_len: usize) -> Vec<Move> {
+    let mut pv = vec![first_move];
+    let mut curr = position.clone();
+    curr.apply(first_move);
+
+    while pv.len() < max_len {
+        match tt.probe(curr.hash()).and_then(|entry| entry.best_move) {
+            Some(mv) if curr.is_legal(mv) => {
+                pv.push(mv);
+                curr.apply(mv);
+            },
+            _ => { break; },
+        }
+    }
+
+    pv
+}
+
+fn outcome_score(outcome: Outcome, ply: i32) -> i32 {
+    match outcome {
+        Outcome::Win(plies) => MATE - ply - plies as i32,
+        Outcome::Loss(plies) => -(MATE - ply - plies as i32),
+        Outcome::Draw => 0,
+    }
+}
+
+// mate scores are stored relative to the node, not to the root
+fn score_to_tt(score: i32, ply: i32) -> i32 {
+    if score > MATE_BOUND { score + ply } else if score < -MATE_BOUND { score - ply } else { score }
+}
+
+fn score_from_tt(score: i32, ply: i32) -> i32 {
+    if score > MATE_BOUND { score - ply } else if score < -MATE_BOUND { score + ply } else { score }
+}
+
+pub const MAX_DEPTH: u8 = 64;
+const SOLVER_BUDGET: usize = 5000;
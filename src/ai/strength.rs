@@ -0,0 +1,49 @@
+use super::search::MAX_DEPTH;
+
+// Levels of the adaptive cpu, from 0 to `MAX_LEVEL` (full strength).
+// The weaker levels search shallower and sometimes play a random move that is only roughly good.
+#[derive(Clone, Copy, Debug)]
+pub struct Strength {
+    pub level: usize,
+    pub max_depth: u8,
+    pub blunder_chance: f32,
+    pub blunder_margin: i32,  // how much worse than the best move a blunder can be
+}
+
+impl Strength {
+    pub fn of_level(level: usize) -> Self {
+        let level = level.min(MAX_LEVEL);
+        let (max_depth, blunder_chance, blunder_margin) = LEVELS[level];
+
+        Strength { level, max_depth, blunder_chance, blunder_margin }
+    }
+
+    // the rating that the level is assumed to play at
+    pub fn rating(&self) -> i32 {
+        LOWEST_RATING + RATING_STEP * self.level as i32
+    }
+
+    // the level that a player of `rating` wins about half of the games against
+    pub fn for_rating(rating: i32) -> Self {
+        let level = ((rating - LOWEST_RATING) as f32 / RATING_STEP as f32).round().max(0.0) as usize;
+
+        Strength::of_level(level)
+    }
+}
+
+pub const MAX_LEVEL: usize = 8;
+
+// (max depth, blunder chance, blunder margin)
+const LEVELS: [(u8, f32, i32); MAX_LEVEL + 1] = [
+    (1, 0.5, 300),
+    (1, 0.35, 200),
+    (2, 0.3, 150),
+    (2, 0.2, 120),
+    (3, 0.15, 100),
+    (3, 0.1, 80),
+    (4, 0.05, 60),
+    (5, 0.0, 0),
+    (MAX_DEPTH, 0.0, 0),
+];
+const LOWEST_RATING: i32 = 700;
+const RATING_STEP: i32 = 150;
@@ -0,0 +1,151 @@
+use crate::position::Move;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Shared by every search thread. Each slot is two words, `key ^ data` and `data`,
+// so that a torn write from another thread is detected as a miss instead of a wrong hit.
+pub struct TranspositionTable {
+    slots: Vec<[AtomicU64; 2]>,
+    mask: usize,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Copy, Clone)]
+pub struct Entry {
+    pub best_move: Option<Move>,
+    pub score: i32,
+    pub depth: u8,
+    pub bound: Bound,
+}
+
+impl TranspositionTable {
+    // `2^bits` slots, 16 bytes each
+    pub fn new(bits: u32) -> Self {
+        let len = 1 << bits;
+
+        TranspositionTable {
+            slots: (0..len).map(|_| [AtomicU64::new(0), AtomicU64::new(0)]).collect(),
+            mask: len - 1,
+        }
+    }
+
+    pub fn probe(&self, hash: u64) -> Option<Entry> {
+        let slot = &self.slots[hash as usize & self.mask];
+        let data = slot[1].load(Ordering::Relaxed);
+
+        if data == 0 || slot[0].load(Ordering::Relaxed) ^ data != hash {
+            return None;
+        }
+
+        Some(unpack(data))
+    }
+
+    pub fn store(&self, hash: u64, entry: Entry) {
+        let slot = &self.slots[hash as usize & self.mask];
+        let old_data = slot[1].load(Ordering::Relaxed);
+
+        // keep deeper results of the same position
+        if old_data != 0 && slot[0].load(Ordering::Relaxed) ^ old_data == hash && unpack(old_data).depth > entry.depth {
+            return;
+        }
+
+        let data = pack(entry);
+        slot[0].store(hash ^ data, Ordering::Relaxed);
+        slot[1].store(data, Ordering::Relaxed);
+    }
+}
+
+// bits 0..16: move, 16..32: score, 32..40: depth, 40..42: bound, 42: always set so that `data != 0`
+fn pack(entry: Entry) -> u64 {
+    let mv = entry.best_move.map(|mv| mv.to_index()).unwrap_or(u16::MAX) as u64;
+    let score = (entry.score.clamp(i16::MIN as i32, i16::MAX as i32) as i16) as u16 as u64;
+    let bound = match entry.bound {
+        Bound::Exact => 0,
+        Bound::Lower => 1,
+        Bound::Upper => 2,
+    };
+
+    mv | score << 16 | (entry.depth as u64) << 32 | bound << 40 | 1 << 42
+}
+
+fn unpack(data: u64) -> Entry {
+    Entry {
+        best_move: Move::from_index(data as u16),
+        score: (data >> 16) as u16 as i16 as i32,
+        depth: (data >> 32) as u8,
+        bound: match (data >> 40) & 3 {
+            0 => Bound::Exact,
+            1 => Bound::Lower,
+            _ => Bound::Upper,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(best_move: Option<Move>, score: i32, depth: u8, bound: Bound) -> Entry {
+        Entry { best_move, score, depth, bound }
+    }
+
+    fn assert_same(a: Entry, b: Entry) {
+        assert_eq!(a.best_move, b.best_move);
+        assert_eq!(a.score, b.score);
+        assert_eq!(a.depth, b.depth);
+        assert!(a.bound == b.bound);
+    }
+
+    #[test]
+    fn pack_round_trip() {
+        let entries = [
+            entry(Some(Move::Step(4, 1)), 0, 0, Bound::Exact),
+            entry(Some(Move::Vertical(8, 7)), -1234, 12, Bound::Lower),
+            entry(Some(Move::Horizontal(7, 8)), i16::MAX as i32, u8::MAX, Bound::Upper),
+            entry(None, i16::MIN as i32, 3, Bound::Exact),
+        ];
+
+        for e in entries {
+            assert_ne!(pack(e), 0);
+            assert_same(unpack(pack(e)), e);
+        }
+
+        // the scores are clamped to 16 bits
+        assert_eq!(unpack(pack(entry(None, 100_000, 1, Bound::Exact))).score, i16::MAX as i32);
+    }
+
+    #[test]
+    fn probe_checks_the_whole_key() {
+        let tt = TranspositionTable::new(4);
+        let hash = 0x1234_5678_9abc_def5;
+        let e = entry(Some(Move::Step(3, 2)), 42, 5, Bound::Lower);
+
+        assert!(tt.probe(hash).is_none());
+
+        tt.store(hash, e);
+        assert_same(tt.probe(hash).unwrap(), e);
+
+        // the same slot, a different position
+        assert!(tt.probe(hash ^ 1 << 40).is_none());
+    }
+
+    #[test]
+    fn store_keeps_the_deeper_result() {
+        let tt = TranspositionTable::new(4);
+        let hash = 0xdead_beef;
+        let deep = entry(Some(Move::Step(1, 1)), 10, 8, Bound::Exact);
+
+        tt.store(hash, deep);
+        tt.store(hash, entry(Some(Move::Step(2, 2)), 20, 3, Bound::Exact));
+        assert_same(tt.probe(hash).unwrap(), deep);
+
+        let deeper = entry(Some(Move::Step(2, 2)), 30, 9, Bound::Upper);
+        tt.store(hash, deeper);
+        assert_same(tt.probe(hash).unwrap(), deeper);
+    }
+}
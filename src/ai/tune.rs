@@ -0,0 +1,118 @@
+use super::eval::{features, Weights, FEATURE_COUNT, FEATURE_NAMES};
+use crate::record::GameRecord;
+
+// Texel tuning: every position of the finished games is labeled with the result for the player to move,
+// and the weights are fitted so that `sigmoid(evaluate / scale)` predicts the labels.
+// The scale is fitted first with the initial weights, so that the new weights stay in the same units.
+pub fn tune(records: &[GameRecord], initial: &Weights, iterations: usize) -> Weights {
+    let samples = collect_samples(records);
+
+    if samples.is_empty() {
+        println!("tune: no finished games to learn from");
+        return initial.clone();
+    }
+
+    let mut weights: [f64; FEATURE_COUNT] = initial.values.map(|value| value as f64);
+    let scale = fit_scale(&samples, &weights);
+    println!("tune: {} positions, scale {:.0}, error {:.5}", samples.len(), scale, mean_error(&samples, &weights, scale));
+
+    // adam
+    let (mut m, mut v) = ([0.0; FEATURE_COUNT], [0.0; FEATURE_COUNT]);
+
+    for iteration in 1..=iterations {
+        let gradient = gradient(&samples, &weights, scale);
+
+        for i in 0..FEATURE_COUNT {
+            m[i] = ADAM_BETA1 * m[i] + (1.0 - ADAM_BETA1) * gradient[i];
+            v[i] = ADAM_BETA2 * v[i] + (1.0 - ADAM_BETA2) * gradient[i] * gradient[i];
+            let m_hat = m[i] / (1.0 - ADAM_BETA1.powi(iteration as i32));
+            let v_hat = v[i] / (1.0 - ADAM_BETA2.powi(iteration as i32));
+            weights[i] -= LEARNING_RATE * m_hat / (v_hat.sqrt() + 1e-12);
+        }
+
+        if iteration % 100 == 0 || iteration == iterations {
+            println!("tune: iteration {}, error {:.5}", iteration, mean_error(&samples, &weights, scale));
+        }
+    }
+
+    let result = Weights {
+        values: weights.map(|weight| weight.round() as i32),
+    };
+
+    for (name, value) in FEATURE_NAMES.iter().zip(result.values.iter()) {
+        println!("  {} {}", name, value);
+    }
+
+    result
+}
+
+// (features, 1.0 if the player to move won the game, 0.0 otherwise)
+fn collect_samples(records: &[GameRecord]) -> Vec<([f64; FEATURE_COUNT], f64)> {
+    let mut result = vec![];
+
+    for record in records.iter() {
+        let (winner, positions) = match (record.winner, record.replay()) {
+            (Some(winner), Ok(positions)) => (winner, positions),
+            _ => { continue; },
+        };
+
+        for position in positions.iter() {
+            if let Some(features) = features(position, None) {
+                let label = if position.player1_turn == winner { 1.0 } else { 0.0 };
+                result.push((features.map(|feature| feature as f64), label));
+            }
+        }
+    }
+
+    result
+}
+
+fn sigmoid(score: f64, scale: f64) -> f64 {
+    1.0 / (1.0 + (-score / scale).exp())
+}
+
+fn score(features: &[f64; FEATURE_COUNT], weights: &[f64; FEATURE_COUNT]) -> f64 {
+    features.iter().zip(weights.iter()).map(|(feature, weight)| feature * weight).sum()
+}
+
+fn mean_error(samples: &[([f64; FEATURE_COUNT], f64)], weights: &[f64; FEATURE_COUNT], scale: f64) -> f64 {
+    samples.iter().map(
+        |(features, label)| (label - sigmoid(score(features, weights), scale)).powi(2)
+    ).sum::<f64>() / samples.len() as f64
+}
+
+fn gradient(samples: &[([f64; FEATURE_COUNT], f64)], weights: &[f64; FEATURE_COUNT], scale: f64) -> [f64; FEATURE_COUNT] {
+    let mut result = [0.0; FEATURE_COUNT];
+
+    for (features, label) in samples.iter() {
+        let prediction = sigmoid(score(features, weights), scale);
+        let common = -2.0 * (label - prediction) * prediction * (1.0 - prediction) / scale;
+
+        for (r, feature) in result.iter_mut().zip(features.iter()) {
+            *r += common * feature;
+        }
+    }
+
+    result.map(|r| r / samples.len() as f64)
+}
+
+fn fit_scale(samples: &[([f64; FEATURE_COUNT], f64)], weights: &[f64; FEATURE_COUNT]) -> f64 {
+    let mut best = (f64::MAX, 100.0);
+    let mut scale = 10.0;
+
+    while scale <= 2000.0 {
+        let error = mean_error(samples, weights, scale);
+
+        if error < best.0 {
+            best = (error, scale);
+        }
+
+        scale *= 1.1;
+    }
+
+    best.1
+}
+
+const LEARNING_RATE: f64 = 1.0;
+const ADAM_BETA1: f64 = 0.9;
+const ADAM_BETA2: f64 = 0.999;
@@ -0,0 +1,234 @@
+use crate::engine::color::Color;
+use crate::engine::graphic::Graphic;
+use crate::engine::inputs::Inputs;
+use crate::mouse_trace::MouseTraces;
+use crate::position::{Move, Position};
+
+// The board of the screens that are not a game, like the puzzles: it draws a `Position`
+// and turns the clicks of a human into legal moves. `Game` shares the drawing and the cursor.
+pub struct Board {
+    pub position: Position,
+    mouse_traces: MouseTraces,
+}
+
+impl Board {
+    pub fn new(position: Position) -> Self {
+        Board {
+            position,
+            mouse_traces: MouseTraces::new(),
+        }
+    }
+
+    // the same place as the board of a game
+    pub fn origin(screen_w: f32, screen_h: f32) -> (f32, f32) {
+        ((screen_w - BOARD_SIZE) / 2.0, (screen_h - BOARD_SIZE) / 1.2)
+    }
+
+    // `Some(Err(message))` for a click that is not a legal move of the player to move
+    // with `accepts_moves` unset, it only leaves the mouse traces
+    pub fn update(&mut self, inputs: &Inputs, mouse_pos: (f32, f32), box_x: f32, box_y: f32, accepts_moves: bool) -> Option<Result<Move, String>> {
+        let mouse_index = get_cursor_index(mouse_pos.0, mouse_pos.1, box_x, box_y);
+        add_mouse_trace(&mut self.mouse_traces, &mouse_index, box_x, box_y);
+
+        if !accepts_moves || !inputs.mouse_pressed[0] {
+            return None;
+        }
+
+        let mv = match mouse_index {
+            Index::Box(x, y) => Move::Step(x as i32, y as i32),
+            Index::Vertical(x, y) => Move::Vertical(x, y),
+            Index::Horizontal(x, y) => Move::Horizontal(x, y),
+            Index::None => { return None; },
+        };
+
+        Some(self.check_move(mv))
+    }
+
+    // the same messages as in a game
+    fn check_move(&self, mv: Move) -> Result<Move, String> {
+        let message = match mv {
+            Move::Step(_, _) if self.position.is_legal(mv) => { return Ok(mv); },
+            Move::Step(_, _) => "Invalid Move!",
+            _ if self.position.curr_walls() == 0 => "No walls to place!",
+            _ if !self.position.is_wall_ok(mv) => "Cannot place a wall there!",
+            _ if !self.position.is_legal(mv) => TRAP_MESSAGE,
+            _ => { return Ok(mv); },
+        };
+
+        Err(message.to_string())
+    }
+
+    pub fn render(&mut self, box_x: f32, box_y: f32) -> Vec<Graphic> {
+        [
+            draw_board(&self.position, box_x, box_y),
+            draw_walls_in_hand(&self.position, box_x, box_y),
+            draw_pawns(&self.position, box_x, box_y),
+            self.mouse_traces.render(),
+        ].concat()
+    }
+}
+
+pub fn draw_board(position: &Position, box_x: f32, box_y: f32) -> Vec<Graphic> {
+    let mut board_graphics = Vec::with_capacity(100);
+
+    board_graphics.push(Graphic::new_round_rect(box_x, box_y, BOARD_SIZE, BOARD_SIZE, 32.0, 0.0, Color::board_normal()));
+
+    for x in 0..9 {
+        for y in 0..9 {
+            board_graphics.push(
+                Graphic::new_round_rect(box_x + (x * 72) as f32 + 18.0, box_y + (y * 72) as f32 + 18.0, 54.0, 54.0, 8.0, 0.0, Color::box_normal())
+            );
+        }
+    }
+
+    for (y, row) in position.horizontal_walls.iter().enumerate() {
+        for (x, wall) in row.iter().enumerate() {
+            if *wall {
+                board_graphics.push(
+                    Graphic::new_rect(box_x + (x * 72) as f32 + 24.0, box_y + (y * 72) as f32 + 5.0, 42.0, 8.0, 0.0, Color::wall())
+                );
+            }
+        }
+    }
+
+    for (x, column) in position.vertical_walls.iter().enumerate() {
+        for (y, wall) in column.iter().enumerate() {
+            if *wall {
+                board_graphics.push(
+                    Graphic::new_rect(box_x + (x * 72) as f32 + 5.0, box_y + (y * 72) as f32 + 24.0, 8.0, 42.0, 0.0, Color::wall())
+                );
+            }
+        }
+    }
+
+    for (x, row) in position.cross_walls.iter().enumerate() {
+        for (y, wall) in row.iter().enumerate() {
+            if *wall {
+                board_graphics.push(
+                    Graphic::new_rect(box_x + (x * 72) as f32 + 5.0, box_y + (y * 72) as f32 + 5.0, 8.0, 8.0, 0.0, Color::wall()),
+                );
+            }
+        }
+    }
+
+    board_graphics
+}
+
+// the pawns, and the squares that the player to move can step to
+pub fn draw_pawns(position: &Position, box_x: f32, box_y: f32) -> Vec<Graphic> {
+    let mut result = vec![
+        Graphic::new_circle(
+            box_x + (position.player1.0 * 72) as f32 + 45.0,
+            box_y + (position.player1.1 * 72) as f32 + 45.0,
+            16.0, 0.0,
+            Color::player1_normal(),
+        ),
+        Graphic::new_circle(
+            box_x + (position.player2.0 * 72) as f32 + 45.0,
+            box_y + (position.player2.1 * 72) as f32 + 45.0,
+            16.0, 0.0,
+            Color::player2_normal(),
+        ),
+    ];
+
+    if position.winner().is_some() {
+        return result;
+    }
+
+    let curr_color = if position.player1_turn {
+        Color::player1_trans()
+    } else {
+        Color::player2_trans()
+    };
+
+    for (x, y) in position.valid_steps() {
+        result.push(
+            Graphic::new_circle(
+                box_x + (x * 72) as f32 + 45.0,
+                box_y + (y * 72) as f32 + 45.0,
+                9.0, 0.0,
+                curr_color.clone(),
+            ),
+        );
+    }
+
+    result
+}
+
+// the same bars on both sides of the board as in a game
+fn draw_walls_in_hand(position: &Position, box_x: f32, box_y: f32) -> Vec<Graphic> {
+    let mut result = vec![];
+
+    for (walls, x) in [(position.walls.0, box_x - 80.0), (position.walls.1, box_x + BOARD_SIZE + 20.0)] {
+        for i in 0..walls {
+            result.push(Graphic::new_rect(x, (i * 72) as f32 + box_y, 60.0, 18.0, 0.0, Color::wall()));
+        }
+    }
+
+    result
+}
+
+pub fn add_mouse_trace(mouse_traces: &mut MouseTraces, mouse_index: &Index, box_x: f32, box_y: f32) {
+    let slot = match *mouse_index {
+        Index::Box(x, y) => Move::Step(x as i32, y as i32),
+        Index::Vertical(x, y) if x < 9 && y < 8 && x > 0 => Move::Vertical(x, y),
+        Index::Horizontal(x, y) if y < 9 && x < 8 && y > 0 => Move::Horizontal(x, y),
+        _ => { return; },
+    };
+    let (x, y, w, h) = slot_rect(slot, box_x, box_y);
+
+    mouse_traces.add(x, y, w, h);
+}
+
+// the same shapes as the mouse traces, pulsing, for the tutorial to point at squares and walls
+pub fn draw_highlights(slots: &[Move], box_x: f32, box_y: f32, frame_count: usize) -> Vec<Graphic> {
+    let mut color = Color::selection_mask();
+    color.a = (((frame_count as f32 / 8.0).sin() + 1.0) * 48.0 + 32.0) as u8;
+
+    slots.iter().map(|slot| {
+        let (x, y, w, h) = slot_rect(*slot, box_x, box_y);
+        Graphic::new_round_rect(x, y, w, h, 12.0, 0.0, color.clone())
+    }).collect()
+}
+
+// the square of a step, or the gap of a wall
+fn slot_rect(slot: Move, box_x: f32, box_y: f32) -> (f32, f32, f32, f32) {
+    match slot {
+        Move::Step(x, y) => (box_x + (x * 72) as f32 + 21.0, box_y + (y * 72) as f32 + 21.0, 48.0, 48.0),
+        Move::Vertical(x, y) => (box_x + (x * 72) as f32 + 3.0, box_y + (y * 72) as f32 + 18.0, 12.0, 126.0),
+        Move::Horizontal(x, y) => (box_x + (x * 72) as f32 + 18.0, box_y + (y * 72) as f32 + 3.0, 126.0, 12.0),
+    }
+}
+
+pub fn get_cursor_index(mouse_x: f32, mouse_y: f32, box_x: f32, box_y: f32) -> Index {
+    if mouse_x <= box_x || mouse_x >= box_x + BOARD_SIZE || mouse_y <= box_y || mouse_y >= box_y + BOARD_SIZE {
+        Index::None
+    }
+
+    else {
+        let (mouse_index_x, mouse_index_y) = ((mouse_x - box_x) as usize / 72, (mouse_y - box_y) as usize / 72);
+        let (mouse_rem_x, mouse_rem_y) = ((mouse_x - box_x) as usize % 72, (mouse_y - box_y) as usize % 72);
+
+        if mouse_rem_x > 18 && mouse_rem_y > 18 {
+            Index::Box(mouse_index_x, mouse_index_y)
+        }
+
+        else if mouse_rem_y <= 18 {
+            Index::Horizontal(mouse_index_x, mouse_index_y)
+        }
+
+        else {
+            Index::Vertical(mouse_index_x, mouse_index_y)
+        }
+    }
+}
+
+pub enum Index {
+    None,
+    Box(usize, usize),
+    Horizontal(usize, usize),
+    Vertical(usize, usize),
+}
+
+pub const BOARD_SIZE: f32 = 666.0;
+pub const TRAP_MESSAGE: &str = "You may not trap a player!";
@@ -0,0 +1,366 @@
+use crate::agent::Agent;
+use crate::ai::{self, agents::{AlphaBetaAgent, MctsAgent}, arena, book::{self, Book}, eval::{Weights, PARAMS_PATH}, mcts::{MctsConfig, RolloutPolicy}, review::{self, Annotation, REVIEW_DEPTH}, search::SearchConfig};
+use crate::record::GameRecord;
+use crate::profile::{self, Profile, DEFAULT_COLOR};
+use crate::puzzle::{self, generator};
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use crate::engine::global::GLOBAL_ENV;
+use crate::engine::file_io::create_dir;
+
+// Handles the command line arguments. It returns `true` if it ran a command
+// that does not need a window, so that `main` can exit right away.
+//
+// quoridor [--threads N] [--book-best] [--no-ponder]   run the game
+// quoridor bench [--threads N] [--depth D]
+// quoridor book [--games N] [--depth D] [--plies P] [--from PATH] [--out PATH] [--save-games DIR]
+// quoridor tune [--games N] [--depth D] [--from PATH] [--iterations K] [--out PATH]
+// quoridor arena [--games N] [--depth D] [--playouts P] [--time MS] [--policy random|greedy|epsilon]
+// quoridor review PATH [--depth D] [--out PATH]
+// quoridor profile add NAME [--color R,G,B]
+// quoridor profile list
+// quoridor puzzle check [PATH]
+// quoridor puzzle generate [--games N] [--depth D] [--from PATH] [--out PATH]
+pub fn run(args: &[String]) -> bool {
+    if let Some(threads) = parse_option(args, "--threads") {
+        unsafe { GLOBAL_ENV.ai_threads = threads; }
+    }
+
+    if args.iter().any(|arg| arg == "--book-best") {
+        unsafe { GLOBAL_ENV.randomize_book = false; }
+    }
+
+    if args.iter().any(|arg| arg == "--no-ponder") {
+        unsafe { GLOBAL_ENV.pondering = false; }
+    }
+
+    match args.first().map(|arg| arg.as_str()) {
+        Some("bench") => {
+            ai::bench::run(ai::thread_count(), parse_option(args, "--depth").unwrap_or(5));
+            true
+        },
+        Some("book") => {
+            build_book(args);
+            true
+        },
+        Some("tune") => {
+            tune(args);
+            true
+        },
+        Some("arena") => {
+            run_arena(args);
+            true
+        },
+        Some("review") => {
+            review_record(args);
+            true
+        },
+        Some("profile") => {
+            manage_profiles(args);
+            true
+        },
+        Some("puzzle") => {
+            match args.get(1).map(|arg| arg.as_str()) {
+                Some("check") => check_puzzles(args),
+                Some("generate") => generate_puzzles(args),
+                _ => eprintln!("usage: quoridor puzzle check [PATH] | quoridor puzzle generate [--games N] [--depth D] [--from PATH] [--out PATH]"),
+            }
+
+            true
+        },
+        _ => false,
+    }
+}
+
+// `--from` imports game records, a file or a directory of them; otherwise it plays `--games` games against itself
+// and `--save-games` keeps the self-play games as records, so that they can be imported later
+fn build_book(args: &[String]) {
+    let plies = parse_option(args, "--plies").unwrap_or(8);
+    let out: String = parse_option(args, "--out").unwrap_or(book::BOOK_PATH.to_string());
+
+    let records = match parse_option::<String>(args, "--from") {
+        Some(path) => match book::load_records(&path) {
+            Ok(records) => records,
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            },
+        },
+        None => book::self_play(
+            parse_option(args, "--games").unwrap_or(20),
+            parse_option(args, "--depth").unwrap_or(3),
+            plies,
+            &Weights::load(PARAMS_PATH).unwrap_or_default(),
+        ),
+    };
+
+    if let Some(dir) = parse_option::<String>(args, "--save-games") {
+        // the book is still built without the records
+        if create_dir(&dir).is_err() {
+            eprintln!("Failed to create directory: {}", dir);
+        }
+
+        else {
+            for (index, record) in records.iter().enumerate() {
+                if let Err(e) = record.save(&format!("{}/game{:04}.txt", dir, index)) {
+                    eprintln!("{}", e);
+                }
+            }
+        }
+    }
+
+    // it adds to the existing book, if there is one
+    let mut opening_book = Book::load(&out).unwrap_or(Book::new());
+    opening_book.add_records(&records, plies);
+
+    match opening_book.save(&out) {
+        Ok(()) => println!("{} positions in `{}`", opening_book.len(), out),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+// fits the evaluation weights to game records, or to self-play games with the current weights
+fn tune(args: &[String]) {
+    let out: String = parse_option(args, "--out").unwrap_or(PARAMS_PATH.to_string());
+    let initial = Weights::load(&out).unwrap_or_default();
+
+    let records = match parse_option::<String>(args, "--from") {
+        Some(path) => match book::load_records(&path) {
+            Ok(records) => records,
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            },
+        },
+        None => book::self_play(
+            parse_option(args, "--games").unwrap_or(50),
+            parse_option(args, "--depth").unwrap_or(2),
+            SELF_PLAY_RANDOM_PLIES,
+            &initial,
+        ),
+    };
+
+    let weights = ai::tune::tune(&records, &initial, parse_option(args, "--iterations").unwrap_or(1000));
+
+    match weights.save(&out) {
+        Ok(()) => println!("saved to `{}`", out),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+// prints the moves that lost something, and writes the annotated record to `--out`
+fn review_record(args: &[String]) {
+    let path = match args.get(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: quoridor review PATH [--depth D] [--out PATH]");
+            return;
+        },
+    };
+
+    let mut record = match GameRecord::load(path) {
+        Ok(record) => record,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        },
+    };
+
+    let depth = parse_option(args, "--depth").unwrap_or(REVIEW_DEPTH);
+    let weights = Weights::load(PARAMS_PATH).unwrap_or_default();
+
+    let result = match review::review(&record.handicap.position(), &record.moves, depth, &weights, &AtomicBool::new(false)) {
+        Some(result) => result,
+        None => {
+            eprintln!("{}: the record has an illegal move", path);
+            return;
+        },
+    };
+
+    for (ply, (mv, annotation)) in record.moves.iter().zip(result.annotations.iter()).enumerate() {
+        if *annotation != Annotation::Best {
+            let best = result.best_moves[ply].map(|mv| mv.to_notation()).unwrap_or(String::from("-"));
+            let player = if (ply % 2 == 0) == record.handicap.player1_first { 1 } else { 2 };
+            println!("{:>3}. p{} {:<4} {} (best: {})", ply + 1, player, mv.to_notation(), annotation.name(), best);
+        }
+    }
+
+    record.annotations = result.annotations;
+    record.scores = result.scores;
+
+    if let Some(out) = parse_option::<String>(args, "--out") {
+        match record.save(&out) {
+            Ok(()) => println!("saved to `{}`", out),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+}
+
+// `add` creates a profile with no games, `list` prints the record of every profile
+fn manage_profiles(args: &[String]) {
+    let profiles = profile::load_profiles();
+
+    match (args.get(1).map(|arg| arg.as_str()), args.get(2)) {
+        (Some("add"), Some(name)) => {
+            if !profile::is_valid_name(name) {
+                eprintln!("invalid name: `{}` (up to 16 letters, digits, `-` or `_`)", name);
+                return;
+            }
+
+            if profiles.iter().any(|profile| &profile.name == name) {
+                eprintln!("`{}` already exists", name);
+                return;
+            }
+
+            let color = match parse_option::<String>(args, "--color") {
+                Some(color) => match color.split(',').map(|c| c.trim().parse::<u8>()).collect::<Result<Vec<u8>, _>>().as_deref() {
+                    Ok([r, g, b]) => (*r, *g, *b),
+                    _ => {
+                        eprintln!("invalid color: `{}`", color);
+                        return;
+                    },
+                },
+                None => DEFAULT_COLOR,
+            };
+
+            match Profile::new(name, color).save() {
+                Ok(()) => println!("added `{}`", name),
+                Err(e) => eprintln!("{}", e),
+            }
+        },
+        (Some("list"), _) => {
+            for profile in profiles.iter() {
+                println!("{}", profile.name);
+
+                for (opponent, record) in profile.records.iter() {
+                    println!(
+                        "    vs {}: {} won, {} lost, {} drawn, {:.1} moves/game, {:.1} walls/game",
+                        opponent, record.wins, record.losses, record.draws, record.average_length(), record.walls_per_game(),
+                    );
+                }
+            }
+        },
+        _ => {
+            eprintln!("usage: quoridor profile add NAME [--color R,G,B] | quoridor profile list");
+        },
+    }
+}
+
+// solves every puzzle of a file, the bundled ones by default, and prints the moves that reach the goal
+// with the difficulty of the puzzles that have exactly one
+fn check_puzzles(args: &[String]) {
+    let puzzles = match args.get(2) {
+        Some(path) => puzzle::load_puzzles(path),
+        None => puzzle::bundled_puzzles(),
+    };
+
+    let puzzles = match puzzles {
+        Ok(puzzles) => puzzles,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        },
+    };
+    let weights = Weights::load(PARAMS_PATH).unwrap_or_default();
+
+    for puzzle in puzzles.iter() {
+        let result = match (puzzle.solutions(), puzzle.position()) {
+            (Some(solutions), Ok(start)) if solutions.len() == 1 => {
+                format!("{} (difficulty {})", solutions[0].to_notation(), generator::difficulty(&start, solutions[0], &weights))
+            },
+            (Some(solutions), _) if solutions.is_empty() => String::from("no solution"),
+            (Some(solutions), _) => solutions.iter().map(|mv| mv.to_notation()).collect::<Vec<String>>().join(" "),
+            (None, _) => String::from("the solver could not tell"),
+        };
+
+        println!("{}: {}", puzzle.id, result);
+    }
+}
+
+// finds puzzles in game records, or in `--games` self-play games, and adds them to `--out`
+// sorted by difficulty, so that the puzzle screen goes from the easy ones to the hard ones
+fn generate_puzzles(args: &[String]) {
+    let out: String = parse_option(args, "--out").unwrap_or(puzzle::PUZZLES_PATH.to_string());
+    let weights = Weights::load(PARAMS_PATH).unwrap_or_default();
+
+    let records = match parse_option::<String>(args, "--from") {
+        Some(path) => match book::load_records(&path) {
+            Ok(records) => records,
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            },
+        },
+        None => book::self_play(
+            parse_option(args, "--games").unwrap_or(20),
+            parse_option(args, "--depth").unwrap_or(3),
+            SELF_PLAY_RANDOM_PLIES,
+            &weights,
+        ),
+    };
+
+    // it adds to the existing puzzles, if there are any
+    let mut puzzles = puzzle::load_puzzles(&out).unwrap_or_default();
+    let count = puzzles.len();
+
+    for found in generator::find_puzzles(&records, &weights) {
+        if puzzles.iter().all(|puzzle| puzzle.id != found.id) {
+            puzzles.push(found);
+        }
+    }
+
+    puzzles.sort_by_key(|puzzle| puzzle.difficulty.unwrap_or(generator::MAX_DIFFICULTY));
+
+    match puzzle::save_puzzles(&puzzles, &out) {
+        Ok(()) => println!("{} new puzzles, {} in `{}`", puzzles.len() - count, puzzles.len(), out),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+// alpha-beta against mcts. With `--time`, both get the same time per move, otherwise
+// alpha-beta searches `--depth` plies and mcts runs `--playouts` playouts.
+fn run_arena(args: &[String]) {
+    let time_limit = parse_option(args, "--time").map(Duration::from_millis);
+    let policy = match parse_option::<String>(args, "--policy") {
+        Some(name) => match RolloutPolicy::from_name(&name) {
+            Some(policy) => policy,
+            None => {
+                eprintln!("unknown rollout policy: `{}`", name);
+                return;
+            },
+        },
+        None => MctsConfig::default().policy,
+    };
+
+    let mut engines: [Box<dyn Agent>; 2] = [
+        Box::new(AlphaBetaAgent::new(SearchConfig {
+            threads: ai::thread_count(),
+            max_depth: if time_limit.is_some() { ai::search::MAX_DEPTH } else { parse_option(args, "--depth").unwrap_or(3) },
+            time_limit,
+            weights: Weights::load(PARAMS_PATH).unwrap_or_default(),
+        })),
+        Box::new(MctsAgent::new(MctsConfig {
+            playouts: if time_limit.is_some() { None } else { Some(parse_option(args, "--playouts").unwrap_or(2000)) },
+            time_limit,
+            policy,
+            ..MctsConfig::default()
+        })),
+    ];
+
+    arena::run(&mut engines, parse_option(args, "--games").unwrap_or(10));
+}
+
+fn parse_option<T: std::str::FromStr>(args: &[String], name: &str) -> Option<T> {
+    let index = args.iter().position(|arg| arg == name)?;
+
+    match args.get(index + 1).map(|value| value.parse()) {
+        Some(Ok(value)) => Some(value),
+        _ => {
+            eprintln!("invalid value for `{}`", name);
+            None
+        },
+    }
+}
+
+// random opening plies of the self-play games for tuning, so that they do not repeat
+const SELF_PLAY_RANDOM_PLIES: usize = 8;
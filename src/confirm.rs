@@ -0,0 +1,93 @@
+use crate::engine::color::Color;
+use crate::engine::context::{Context, Cover};
+use crate::engine::global::global_env;
+use crate::engine::graphic::Graphic;
+use crate::engine::inputs::Inputs;
+use crate::engine::keys::{KEY_ENTER, KEY_ESCAPE};
+use crate::engine::sound::SoundAction;
+use crate::engine::widget::{button::Button, textbox::TextBox};
+use crate::screen::{self, ScreenScale};
+use std::any::Any;
+
+// A yes-or-no question over another screen, which keeps running under it without the inputs.
+// "Yes" (or Enter) pops it with `answer`, which the screen under it gets in `uncover`, and "No" (or ESC) pops it with nothing.
+pub struct Confirm<T> {
+    question: String,
+    answer: Option<T>,  // taken when it's given
+    is_closed: bool,
+    buttons: Vec<Button>,  // yes, no
+    screen_scale: Option<ScreenScale>,
+    frame_count: usize,
+}
+
+impl<T: Any> Confirm<T> {
+    pub fn new(question: &str, answer: T) -> Self {
+        Confirm {
+            question: question.to_string(),
+            answer: Some(answer),
+            is_closed: false,
+            buttons: vec![Button::new(0.0, 0.0, "Yes"), Button::new(0.0, 0.0, "No")],
+            screen_scale: ScreenScale::calc(),
+            frame_count: 0,
+        }
+    }
+}
+
+impl<T: Any> Context for Confirm<T> {
+    fn frame(mut self: Box<Self>, inputs: Inputs) -> (Box<dyn Context>, Vec<Graphic>, Vec<SoundAction>) {
+        self.frame_count += 1;
+
+        if inputs.is_screen_size_changed || self.frame_count & 7 == 7 {
+            self.screen_scale = ScreenScale::calc();
+        }
+
+        let (screen_w, screen_h) = screen::screen_size(&self.screen_scale);
+        let mouse_pos = screen::scale_mouse(&self.screen_scale, inputs.mouse_pos);
+        let (panel_x, panel_y) = ((screen_w - PANEL_W) / 2.0, (screen_h - PANEL_H) / 2.0);
+        let mut clicked = None;
+
+        for (index, button) in self.buttons.iter_mut().enumerate() {
+            button.move_to(panel_x + 40.0 + index as f32 * 200.0, panel_y + PANEL_H - 70.0);
+
+            if button.check_mouse(mouse_pos) && inputs.mouse_pressed[0] {
+                clicked = Some(index);
+            }
+        }
+
+        if inputs.key_pressed[KEY_ENTER] {
+            clicked = Some(0);
+        }
+
+        if inputs.key_pressed[KEY_ESCAPE] {
+            clicked = Some(1);
+        }
+
+        // it's popped at the end of the frame, so it must not ask twice
+        if clicked.is_some() && !self.is_closed {
+            self.is_closed = true;
+
+            let result = if clicked == Some(0) { self.answer.take().map(|answer| Box::new(answer) as Box<dyn Any>) } else { None };
+            global_env().pop_context(result);
+        }
+
+        let graphics = [
+            vec![
+                Graphic::new_rect(0.0, 0.0, screen_w, screen_h, 0.0, Color::new(0, 0, 0, 96)),
+                Graphic::new_round_rect(panel_x, panel_y, PANEL_W, PANEL_H, 16.0, 0.0, Color::new(16, 16, 16, 224)),
+            ],
+            TextBox::new(&self.question, panel_x, panel_y + 30.0, PANEL_W, 60.0, 28.0).set_color(Color::ui()).align_center().render(),
+            self.buttons.iter_mut().flat_map(|button| button.render()).collect(),
+        ].concat();
+
+        let graphics = screen::scale_screen(&self.screen_scale, graphics);
+
+        (self, graphics, vec![])
+    }
+
+    fn cover(&self) -> Cover {
+        Cover::Overlay { block_input: true, block_updates: false }
+    }
+}
+
+const PANEL_W: f32 = 440.0;
+const PANEL_H: f32 = 180.0;
@@ -1,62 +1,51 @@
 use crate::game::Game;
 use crate::engine::graphic::Graphic;
 use crate::engine::color::Color;
+use crate::engine::widget::textbox::TextBox;
+use crate::position::Position;
 
 impl Game {
-    pub fn dfs(&self, from: (i32, i32), destination: i32, desired_direction: i32, visited: &mut Vec<Vec<bool>>) -> bool {
-        if from.0 == -1 || from.1 == -1 || from.0 == 9 || from.1 == 9 {
-            false
+    // each player's shortest path to the goal, with the number of steps left at its end
+    pub fn vis_paths(&self, box_x: f32, box_y: f32) -> Vec<Graphic> {
+        if !self.show_paths {
+            return vec![];
         }
 
-        else if from.0 == destination {
-            visited[from.0 as usize][from.1 as usize] = true;
-            true
-        }
-
-        else {
-            visited[from.0 as usize][from.1 as usize] = true;
-            let dirs = vec![
-                (desired_direction, 0),
-                (-desired_direction, 0),
-                (0, 1),
-                (0, -1),
-            ];
-            let mut result = false;
-
-            for dir in dirs.iter() {
-                if self.is_movable_at(from, *dir) && from.0 + dir.0 >= 0 && from.1 + dir.1 >= 0 && !visited[(from.0 + dir.0) as usize][(from.1 + dir.1) as usize] {
-                    result |= self.dfs((from.0 + dir.0, from.1 + dir.1), destination, desired_direction, visited);
-
-                    if result {
-                        break;
-                    }
-                }
-            }
-
-            result
-        }
-    }
-
-    pub fn vis_dfs(&self, box_x: f32, box_y: f32) -> Vec<Graphic> {
-        let mut visited = vec![vec![false; 10]; 10];
-
-        self.dfs(self.player1.position, 8, 1, &mut visited);
-
-        let mut result = Vec::with_capacity(100);
-
-        for x in 0..10 {
-            for y in 0..10 {
-                if visited[x][y] {
-                    result.push(Graphic::new_rect(
-                        box_x + (x * 72) as f32 + 18.0,
-                        box_y + (y * 72) as f32 + 18.0,
-                        54.0, 54.0, 0.0,
-                        Color::new(192, 64, 64, 128),
-                    ));
-                }
+        let position = Position::from_game(self);
+        let mut result = Vec::with_capacity(40);
+
+        for (player1, color, text_color) in [(true, Color::player1_trans(), Color::player1_normal()), (false, Color::player2_trans(), Color::player2_normal())] {
+            let path = match position.shortest_path(player1) {
+                Some(path) => path,
+                None => { continue; },
+            };
+
+            // the pawn's own cell is not a step
+            result.push(draw_cells(&path[1..], color, box_x, box_y));
+
+            if let Some((x, y)) = path.last() {
+                result.push(TextBox::new(
+                    &format!("{}", path.len() - 1),
+                    box_x + (x * 72) as f32 + 18.0,
+                    box_y + (y * 72) as f32 + 18.0,
+                    54.0,
+                    54.0,
+                    28.0,
+                ).set_color(text_color).align_center().render());
             }
         }
 
-        result
+        result.concat()
     }
 }
+
+pub fn draw_cells(cells: &[(i32, i32)], color: Color, box_x: f32, box_y: f32) -> Vec<Graphic> {
+    cells.iter().map(
+        |(x, y)| Graphic::new_rect(
+            box_x + (x * 72) as f32 + 18.0,
+            box_y + (y * 72) as f32 + 18.0,
+            54.0, 54.0, 0.0,
+            color.clone(),
+        )
+    ).collect()
+}
@@ -8,7 +8,9 @@ pub mod inputs;
 pub mod keys;
 pub mod loader;
 pub mod sound;
+pub mod stack;
 pub mod transform;
+pub mod transition;
 pub mod widget;
 
 #[cfg(feature = "profile")]
@@ -1,7 +1,25 @@
 use super::sound::SoundAction;
 use super::graphic::Graphic;
 use super::inputs::Inputs;
+use std::any::Any;
 
 pub trait Context {
     fn frame(self: Box<Self>, inputs: Inputs) -> (Box<dyn Context>, Vec<Graphic>, Vec<SoundAction>);
+
+    // how the contexts under this one run while it's on top of the stack (see `stack::ContextStack`)
+    fn cover(&self) -> Cover {
+        Cover::Opaque
+    }
+
+    // when the context above it is popped, with what that context left (`GLOBAL_ENV.pop_context`)
+    fn uncover(&mut self, _result: Option<Box<dyn Any>>) {}
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Cover {
+    Opaque,  // the contexts under it are neither drawn nor run
+    Overlay {
+        block_input: bool,  // the contexts under it run without the clicks and the keys
+        block_updates: bool,  // the contexts under it don't run, and their last frame is drawn
+    },
 }
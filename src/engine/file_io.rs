@@ -148,3 +148,12 @@ pub fn write_to_file(path: &str, bytes: &[u8]) -> Result<(), ()> {
     }
 
 }
+
+pub fn create_dir(path: &str) -> Result<(), ()> {
+
+    match fs::create_dir_all(path) {
+        Err(_) => Err(()),
+        Ok(_) => Ok(())
+    }
+
+}
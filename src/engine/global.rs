@@ -1,21 +1,39 @@
 #![allow(dead_code)]
 
+use super::context::Context;
+use std::any::Any;
+
 pub struct GlobalEnv {
     pub messages: Vec<EnvMessage>,
     pub screen_size: (f32, f32),
     pub is_font_available: bool,
 
     // add more fields for your game!
+    pub ai_threads: usize,  // 0: as many as the cpu has
+    pub randomize_book: bool,
+    pub pondering: bool,  // whether the cpu thinks on the opponent's time
+    pub fade_frames: usize,  // of each half of `transition::fade`
 }
 
 pub static mut GLOBAL_ENV: GlobalEnv = GlobalEnv::new();
 
+// `GLOBAL_ENV` through a raw pointer, so that the callers don't make a reference to the static themselves.
+// Like the static, it's only for the main thread.
+pub fn global_env() -> &'static mut GlobalEnv {
+    let env = &raw mut GLOBAL_ENV;
+    unsafe { &mut *env }
+}
+
 impl GlobalEnv {
     pub const fn new() -> Self {
         GlobalEnv {
             messages: vec![],
             is_font_available: false,
             screen_size: (0.0, 0.0),
+            ai_threads: 0,
+            randomize_book: true,
+            pondering: true,
+            fade_frames: 10,
         }
     }
 
@@ -49,6 +67,16 @@ impl GlobalEnv {
     pub fn grab_cursor(&mut self, grab: bool) {
         self.messages.push(EnvMessage::GrabCursor(grab));
     }
+
+    // puts `context` on top of the current one, which keeps its state until `context` is popped
+    pub fn push_context(&mut self, context: Box<dyn Context>) {
+        self.messages.push(EnvMessage::PushContext(context));
+    }
+
+    // removes the context on top, and hands `result` to the one under it
+    pub fn pop_context(&mut self, result: Option<Box<dyn Any>>) {
+        self.messages.push(EnvMessage::PopContext(result));
+    }
 }
 
 pub enum EnvMessage {
@@ -58,4 +86,6 @@ pub enum EnvMessage {
     LoadSound(String, usize),  // path, index
     ShowCursor(bool),
     GrabCursor(bool),
+    PushContext(Box<dyn Context>),
+    PopContext(Option<Box<dyn Any>>),
 }
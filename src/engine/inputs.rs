@@ -1,5 +1,8 @@
 use macroquad::prelude::*;
+use macroquad::input::utils::{register_input_subscriber, repeat_all_miniquad_input};
+use macroquad::miniquad::EventHandler;
 
+#[derive(Clone)]
 pub struct Inputs {
     pub key_down: Vec<bool>,
     pub key_pressed: Vec<bool>,
@@ -8,7 +11,8 @@ pub struct Inputs {
     pub mouse_pressed: [bool;3],
     pub mouse_released: [bool;3],
     pub mouse_down: [bool;3],
-    pub is_screen_size_changed: bool
+    pub is_screen_size_changed: bool,
+    pub is_focused: bool,  // set by `Focus::poll`
 }
 
 impl Inputs {
@@ -33,9 +37,68 @@ impl Inputs {
                 is_mouse_button_down(MouseButton::Middle),
                 is_mouse_button_down(MouseButton::Right),
             ],
-            is_screen_size_changed
+            is_screen_size_changed,
+            is_focused: true,
         }
     }
+
+    // the same frame without the clicks and the keys, for the contexts that don't get them
+    pub fn without_presses(self) -> Self {
+        Inputs {
+            key_down: vec![false; self.key_down.len()],
+            key_pressed: vec![false; self.key_pressed.len()],
+            mouse_wheel: 0.0,
+            mouse_pressed: [false; 3],
+            mouse_released: [false; 3],
+            mouse_down: [false; 3],
+            ..self
+        }
+    }
+}
+
+// Whether the window has the focus. miniquad reports a window that loses the focus as minimized on most desktops,
+// but not always the way back, so a click or a key in the window also counts as having the focus again.
+pub struct Focus {
+    subscriber: usize,
+    events: FocusEvents,
+}
+
+struct FocusEvents {
+    is_focused: bool,
+}
+
+impl Focus {
+    pub fn new() -> Self {
+        Focus {
+            subscriber: register_input_subscriber(),
+            events: FocusEvents { is_focused: true },
+        }
+    }
+
+    // once a frame
+    pub fn poll(&mut self, inputs: &Inputs) -> bool {
+        repeat_all_miniquad_input(&mut self.events, self.subscriber);
+
+        if inputs.mouse_pressed.iter().any(|pressed| *pressed) || inputs.key_pressed.iter().any(|pressed| *pressed) {
+            self.events.is_focused = true;
+        }
+
+        self.events.is_focused
+    }
+}
+
+impl EventHandler for FocusEvents {
+    fn update(&mut self) {}
+
+    fn draw(&mut self) {}
+
+    fn window_minimized_event(&mut self) {
+        self.is_focused = false;
+    }
+
+    fn window_restored_event(&mut self) {
+        self.is_focused = true;
+    }
 }
 
 pub fn get_down_keys() -> Vec<bool> {
@@ -0,0 +1,97 @@
+use super::context::{Context, Cover};
+use super::graphic::Graphic;
+use super::inputs::Inputs;
+use super::sound::SoundAction;
+use std::any::Any;
+
+// The contexts that the main loop runs. Only the top one gets the inputs, and it can put another context
+// over itself with `global_env().push_context`, or take itself off with `global_env().pop_context`.
+// A context that returns another one from `frame` replaces itself, in its place in the stack.
+// The contexts under the top run and draw as the `Cover`s above them let them: an opaque context hides
+// everything under it, and an overlay is drawn over the contexts under it.
+pub struct ContextStack {
+    layers: Vec<Layer>,
+}
+
+struct Layer {
+    context: Option<Box<dyn Context>>,  // `None` only while it runs
+    last_frame: Vec<Graphic>,  // for the overlays that block the updates
+}
+
+impl ContextStack {
+    pub fn new(context: Box<dyn Context>) -> Self {
+        ContextStack {
+            layers: vec![Layer::new(context)],
+        }
+    }
+
+    pub fn push(&mut self, context: Box<dyn Context>) {
+        self.layers.push(Layer::new(context));
+    }
+
+    // the bottom context is never popped
+    pub fn pop(&mut self, result: Option<Box<dyn Any>>) {
+        if self.layers.len() < 2 {
+            return;
+        }
+
+        self.layers.pop();
+
+        if let Some(context) = self.layers.last_mut().and_then(|layer| layer.context.as_mut()) {
+            context.uncover(result);
+        }
+    }
+
+    // every context goes, like after an error
+    pub fn reset(&mut self, context: Box<dyn Context>) {
+        self.layers = vec![Layer::new(context)];
+    }
+
+    pub fn frame(&mut self, inputs: Inputs) -> (Vec<Graphic>, Vec<SoundAction>) {
+        let covers: Vec<Cover> = self.layers.iter().map(|layer| layer.cover()).collect();
+
+        // the lowest visible layer is the top one, or the last opaque one under it
+        let bottom = covers.iter().rposition(|cover| *cover == Cover::Opaque).unwrap_or(0);
+        let top = self.layers.len() - 1;
+
+        let mut graphics = vec![];
+        let mut sound_actions = vec![];
+
+        for index in bottom..=top {
+            let (block_input, block_updates) = covers[index + 1..].iter().fold((false, false), |(input, updates), cover| match cover {
+                Cover::Overlay { block_input, block_updates } => (input || *block_input, updates || *block_updates),
+                Cover::Opaque => (input, updates),
+            });
+
+            let layer = &mut self.layers[index];
+
+            if !block_updates {
+                let layer_inputs = if block_input { inputs.clone().without_presses() } else { inputs.clone() };
+
+                if let Some(context) = layer.context.take() {
+                    let (context, layer_graphics, layer_sounds) = context.frame(layer_inputs);
+                    layer.context = Some(context);
+                    layer.last_frame = layer_graphics;
+                    sound_actions.extend(layer_sounds);
+                }
+            }
+
+            graphics.extend(layer.last_frame.iter().cloned());
+        }
+
+        (graphics, sound_actions)
+    }
+}
+
+impl Layer {
+    fn new(context: Box<dyn Context>) -> Self {
+        Layer {
+            context: Some(context),
+            last_frame: vec![],
+        }
+    }
+
+    fn cover(&self) -> Cover {
+        self.context.as_ref().map(|context| context.cover()).unwrap_or(Cover::Opaque)
+    }
+}
@@ -0,0 +1,73 @@
+use super::color::Color;
+use super::context::Context;
+use super::global::GLOBAL_ENV;
+use super::graphic::Graphic;
+use super::inputs::Inputs;
+use super::sound::SoundAction;
+
+// Switching from one context to another. A context returns `cut(next, graphics)` or `fade(next, graphics)`
+// from its `frame` instead of `(next, graphics, vec![])`.
+// `fade` darkens the last frame of the old context, then brightens the new context from black.
+// The new context runs during the second half, but the clicks and the keys don't reach it until the fade is over.
+// `GLOBAL_ENV.fade_frames` is the length of each half, and 0 makes every fade a cut.
+pub struct Fade {
+    from: Vec<Graphic>,  // the last frame of the old context
+    to: Box<dyn Context>,
+    frame: usize,
+    half: usize,
+}
+
+pub fn cut(to: Box<dyn Context>, graphics: Vec<Graphic>) -> (Box<dyn Context>, Vec<Graphic>, Vec<SoundAction>) {
+    (to, graphics, vec![])
+}
+
+pub fn fade(to: Box<dyn Context>, graphics: Vec<Graphic>) -> (Box<dyn Context>, Vec<Graphic>, Vec<SoundAction>) {
+    let half = unsafe { GLOBAL_ENV.fade_frames };
+
+    if half == 0 {
+        return cut(to, graphics);
+    }
+
+    let fade = Fade {
+        from: graphics.clone(),
+        to,
+        frame: 0,
+        half,
+    };
+
+    (Box::new(fade), graphics, vec![])
+}
+
+impl Context for Fade {
+    fn frame(mut self: Box<Self>, inputs: Inputs) -> (Box<dyn Context>, Vec<Graphic>, Vec<SoundAction>) {
+        self.frame += 1;
+
+        // fading out: the old frame, frozen
+        if self.frame <= self.half {
+            let darkness = self.frame as f32 / self.half as f32;
+            let graphics = [self.from.clone(), vec![cover(darkness)]].concat();
+
+            return (self, graphics, vec![]);
+        }
+
+        let inputs = inputs.without_presses();
+        let Fade { from, to, frame, half } = *self;
+        let (to, graphics, sound_actions) = to.frame(inputs);
+        let darkness = 1.0 - (frame - half) as f32 / half as f32;
+
+        if frame >= half * 2 {
+            return (to, graphics, sound_actions);
+        }
+
+        let graphics = [graphics, vec![cover(darkness)]].concat();
+
+        (Box::new(Fade { from, to, frame, half }), graphics, sound_actions)
+    }
+}
+
+// black over the whole window, `darkness` from 0 to 1
+fn cover(darkness: f32) -> Graphic {
+    let (screen_w, screen_h) = unsafe { GLOBAL_ENV.screen_size };
+
+    Graphic::new_rect(0.0, 0.0, screen_w, screen_h, 0.0, Color::new(0, 0, 0, (darkness.clamp(0.0, 1.0) * 255.0) as u8))
+}
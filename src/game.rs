@@ -1,10 +1,25 @@
 mod save_data;
 mod graphic;
-
+mod seat;
+mod spectator;
+mod explain;
+mod heatmap;
+mod review;
+mod summary;
+mod setup;
+mod series;
+mod ending;
+mod analysis;
+mod saved_game;
+mod pause;
+
+use crate::confirm::Confirm;
 use crate::engine::context::Context;
+use crate::engine::transition;
 use crate::engine::inputs::Inputs;
 use crate::engine::graphic::Graphic;
-use crate::engine::global::GLOBAL_ENV;
+use crate::engine::global::{global_env, GLOBAL_ENV};
+use crate::engine::keys::{KEY_E, KEY_ESCAPE, KEY_H, KEY_P};
 use crate::engine::widget::{
     textbox::TextBox,
     button::Button,
@@ -14,7 +29,30 @@ use crate::engine::color::Color;
 use crate::player::Player;
 use crate::popup::Popup;
 use crate::mouse_trace::MouseTraces;
+use crate::position::{Move, Position};
+use crate::record::{EndReason, GameRecord};
+use crate::rating::{Rating, RATING_PATH};
+use crate::profile::{self, Profile};
+use crate::puzzle::{self, Puzzle};
+use crate::menu::MainMenu;
+use crate::settings::{Settings, SETTINGS_PATH};
+use crate::handicap::Handicap;
+use crate::screen::{self, ScreenScale};
+use crate::board::{add_mouse_trace, get_cursor_index, Index, BOARD_SIZE, TRAP_MESSAGE};
+use crate::ai::{self, agents::AlphaBetaAgent, personality::Personality, strength::Strength, book::{Book, BOOK_PATH}, eval::{Weights, PARAMS_PATH}, search::{SearchConfig, MAX_DEPTH}};
 use save_data::GameSaveData;
+use seat::Seat;
+use spectator::Spectator;
+pub use ending::TIME_CONTROLS;
+use explain::Explanation;
+use review::ReviewState;
+use summary::Summary;
+use setup::Setup;
+use pause::{PauseMenu, Unpaused};
+use series::Series;
+use std::any::Any;
+use std::sync::Arc;
+use std::thread::JoinHandle;
 use std::time;
 
 pub struct Game {
@@ -28,8 +66,34 @@ pub struct Game {
     pub cross_walls: Vec<Vec<bool>>,
     curr_popup: Popup,
     pub player1_turn: bool,
-    played_by_cpu: (bool, bool),  // (player1, player2)
-    last_turn_data: GameSaveData,
+    seats: (Seat, Seat),  // (player1, player2)
+    spectator: Option<Spectator>,  // `Some` when both seats are cpu
+    history: Vec<GameSaveData>,
+    moves: Vec<Move>,  // the moves that led to the current position, for the game record
+    book: Arc<Book>,
+    weights: Weights,
+    personality: Personality,  // of the cpu in the next game
+    adaptive: bool,  // whether the cpu in the next game plays at the human's level
+    rating: Rating,
+    explanation: Option<Explanation>,  // of the last move, if the cpu played it
+    show_explanation: bool,
+    review: Option<ReviewState>,  // of the finished game
+    summary: Option<Summary>,
+    setup: Option<Setup>,  // the new-game dialog, when it's open
+    series: Option<Series>,  // the match, if the game is a part of one
+    outcome: Option<(Option<bool>, EndReason)>,  // (winner, reason) once the game is over, `None` as the winner for a draw
+    draw_offer: Option<bool>,  // the player who offered a draw to the other human, until the other one moves
+    draw_answer: Option<(u64, JoinHandle<bool>)>,  // the cpu thinking about a draw offer, in the position with this hash
+    position_hashes: Vec<u64>,  // of every position in the game, for the repetition draws
+    move_limit: Option<usize>,  // the game is a draw after this many moves, in this game and its rematches
+    time_control: Option<usize>,  // seconds for each player, in this game and its rematches
+    handicap: Handicap,  // of this game and its rematches
+    profiles: Vec<Profile>,
+    puzzles: Vec<Puzzle>,  // the bundled and the generated ones
+    open_menu: bool,  // the main menu takes over the screen after this frame
+    pub show_paths: bool,  // the shortest-path overlay
+    show_heatmap: bool,  // the wall-threat overlay
+    forced_win: Option<(bool, u8)>,  // (is player 1, number of moves), found by the endgame solver
     last_state: GameState,  // state to transit from `ScreenTooSmall`
     buttons: Vec<Button>,
     screen_scale: Option<ScreenScale>,
@@ -37,10 +101,10 @@ pub struct Game {
     frame_count: usize,
 }
 
-struct ScreenScale {
-    offset_x: f32,
-    offset_y: f32,
-    zoom: f32,
+// the questions that the game asks in a `Confirm` over itself
+#[derive(Copy, Clone, PartialEq)]
+enum Confirmed {
+    Resign,
 }
 
 #[derive(Copy, Clone, PartialEq)]
@@ -54,8 +118,13 @@ impl Game {
     pub fn new() -> Self {
         let restart_button_vcpu = Button::new(0.0, 0.0, "New: vs CPU");
         let restart_button_vperson = Button::new(0.0, 0.0, "New: vs HUMAN");
+        let spectate_button = Button::new(0.0, 0.0, "Watch: CPU vs CPU");
         let undo_button = Button::new(0.0, 0.0, "Undo");
-        let quit_button = Button::new(0.0, 0.0, "Quit");
+        let resign_button = Button::new(0.0, 0.0, "Resign");
+        let draw_button = Button::new(0.0, 0.0, "Offer draw");
+        let paths_button = Button::new(0.0, 0.0, &paths_label(false));
+        let heatmap_button = Button::new(0.0, 0.0, &heatmap_label(false));
+        let menu_button = Button::new(0.0, 0.0, "Menu");
 
         let mut game = Game {
             state: GameState::Playing,
@@ -68,20 +137,59 @@ impl Game {
             clock: 0.0,
             curr_popup: Popup::dummy(),
             player1_turn: true,
-            played_by_cpu: (false, false),
-            last_turn_data: GameSaveData::dummy(),
+            seats: (Seat::human(), Seat::human()),
+            spectator: None,
+            history: vec![],
+            moves: vec![],
+            book: Arc::new(Book::load(BOOK_PATH).unwrap_or(Book::new())),  // it's okay to play without a book
+            weights: Weights::load(PARAMS_PATH).unwrap_or_default(),
+            personality: Personality::Balanced,
+            adaptive: false,
+            rating: Rating::load(RATING_PATH).unwrap_or_default(),
+            explanation: None,
+            show_explanation: true,
+            review: None,
+            summary: None,
+            setup: None,
+            series: None,
+            outcome: None,
+            draw_offer: None,
+            draw_answer: None,
+            position_hashes: vec![Position::new().hash()],
+            move_limit: None,
+            time_control: None,
+            handicap: Handicap::default(),
+            profiles: profile::load_profiles(),
+            puzzles: puzzle::all_puzzles().unwrap_or_else(|e| {
+                unsafe { GLOBAL_ENV.raise_error(&e); }
+                vec![]
+            }),
+            open_menu: false,
+            show_paths: false,
+            show_heatmap: false,
+            forced_win: None,
             last_state: GameState::Playing,
             buttons: vec![
                 restart_button_vcpu,
                 restart_button_vperson,
+                spectate_button,
                 undo_button,
-                quit_button,
+                resign_button,
+                draw_button,
+                paths_button,
+                heatmap_button,
+                menu_button,
             ],
             screen_scale: None,
             mouse_traces: MouseTraces::new(),
             frame_count: 0,
         };
 
+        // it's okay to play with the default settings
+        let settings = Settings::load(SETTINGS_PATH).unwrap_or_default();
+        settings.apply();
+        game.apply_settings(&settings);
+
         game.calc_screen_scale();
         game.locate_buttons();
 
@@ -99,28 +207,208 @@ impl Game {
         }
     }
 
+    // for the contexts that the game opens, like the puzzles
+    pub(crate) fn puzzles(&self) -> &[Puzzle] {
+        &self.puzzles
+    }
+
+    pub(crate) fn profiles(&self) -> &[Profile] {
+        &self.profiles
+    }
+
+    pub(crate) fn profiles_mut(&mut self) -> &mut [Profile] {
+        &mut self.profiles
+    }
+
+    // the profile of the last human player, if they had one
+    pub(crate) fn last_profile(&self) -> Option<usize> {
+        self.seats.0.profile.or(self.seats.1.profile)
+    }
+
+    pub(crate) fn settings(&self) -> Settings {
+        Settings {
+            show_paths: self.show_paths,
+            show_heatmap: self.show_heatmap,
+            show_explanation: self.show_explanation,
+            transitions: unsafe { GLOBAL_ENV.fade_frames > 0 },
+        }
+    }
+
+    pub(crate) fn apply_settings(&mut self, settings: &Settings) {
+        if self.show_paths != settings.show_paths {
+            self.toggle_paths();
+        }
+
+        if self.show_heatmap != settings.show_heatmap {
+            self.toggle_heatmap();
+        }
+
+        self.show_explanation = settings.show_explanation;
+    }
+
+    // when the screen comes back to the game, the time away does not count on the clock
+    pub(crate) fn resume(&mut self) {
+        self.last_clock_tick = time::Instant::now();
+    }
+
     fn undo(&mut self) {
-        self.player1 = self.last_turn_data.player1.clone();
-        self.player2 = self.last_turn_data.player2.clone();
-        self.clock = self.last_turn_data.clock;
-        self.vertical_walls = self.last_turn_data.vertical_walls.clone();
-        self.horizontal_walls = self.last_turn_data.horizontal_walls.clone();
-        self.cross_walls = self.last_turn_data.cross_walls.clone();
-        self.player1_turn = self.last_turn_data.player1_turn;
+        // the start of a game with a handicap is not the normal start, so there's nothing to go back to
+        let last_turn_data = match self.history.pop() {
+            Some(data) => data,
+            None => { return; },
+        };
+        self.moves.pop();
+        self.outcome = None;
+        self.explanation = None;
+
+        if self.position_hashes.len() > 1 {
+            self.position_hashes.pop();
+        }
+
+        self.cancel_review();
+        self.summary = None;
+
+        self.cancel_agents();
+
+        self.player1 = last_turn_data.player1;
+        self.player2 = last_turn_data.player2;
+        self.clock = last_turn_data.clock;
+        self.vertical_walls = last_turn_data.vertical_walls;
+        self.horizontal_walls = last_turn_data.horizontal_walls;
+        self.cross_walls = last_turn_data.cross_walls;
+        self.draw_offer = last_turn_data.draw_offer;
+        self.player1_turn = last_turn_data.player1_turn;
+        self.update_analysis();
 
         self.last_clock_tick = time::Instant::now();
     }
 
-    fn restart(&mut self, played_by_cpu: (bool, bool)) {
-        self.player1 = Player::new(true);
-        self.player2 = Player::new(false);
+    // against the cpu, it takes back the cpu's move too
+    // when spectating, it takes back a single move and pauses
+    fn undo_turn(&mut self) {
+        if self.state == GameState::GameOver {
+            self.uncount_series_game();
+        }
+
+        self.undo();
+
+        while !self.is_human_turn() && !self.history.is_empty() && self.spectator.is_none() {
+            self.undo();
+        }
+
+        if !self.is_paused() {
+            if let Some(spectator) = &mut self.spectator {
+                spectator.toggle_pause();
+            }
+        }
+
+        self.state = GameState::Playing;
+        self.last_state = GameState::Playing;
+    }
+
+    // a wall of the human to move, checked before it's played, so that nobody sees a trapped pawn
+    fn place_wall(&mut self, mv: Move) {
+        let is_wall_ok = match mv {
+            Move::Vertical(x, y) => self.is_vertical_wall_ok_at(x, y),
+            Move::Horizontal(x, y) => self.is_horizontal_wall_ok_at(x, y),
+            Move::Step(_, _) => false,
+        };
+
+        if (self.player1_turn && self.player1.walls == 0) || (!self.player1_turn && self.player2.walls == 0) {
+            self.curr_popup = Popup::new("No walls to place!");
+        }
+
+        else if !is_wall_ok {
+            self.curr_popup = Popup::new("Cannot place a wall there!");
+        }
+
+        else if !Position::from_game(self).is_legal(mv) {
+            self.curr_popup = Popup::new(TRAP_MESSAGE);
+        }
+
+        else {
+            self.play_move(mv);
+        }
+    }
+
+    // it does not check whether the move is valid
+    pub fn play_move(&mut self, mv: Move) {
+        self.history.push(GameSaveData::from_game(self));
+        self.moves.push(mv);
+        self.explanation = None;
+
+        // making a move declines the other player's offer
+        if self.draw_offer == Some(!self.player1_turn) {
+            self.draw_offer = None;
+        }
+
+        match mv {
+            Move::Step(x, y) => {
+                if self.player1_turn {
+                    self.player1.move_to(x, y);
+                }
+
+                else {
+                    self.player2.move_to(x, y);
+                }
+            },
+            Move::Vertical(x, y) => {
+                self.vertical_walls[x][y] = true;
+                self.vertical_walls[x][y + 1] = true;
+                self.cross_walls[x][y + 1] = true;
+                self.use_wall();
+            },
+            Move::Horizontal(x, y) => {
+                self.horizontal_walls[y][x] = true;
+                self.horizontal_walls[y][x + 1] = true;
+                self.cross_walls[x + 1][y] = true;
+                self.use_wall();
+            },
+        }
+
+        self.next_turn();
+        self.position_hashes.push(Position::from_game(self).hash());
+        self.update_analysis();
+        self.notify_agents();
+    }
+
+    fn use_wall(&mut self) {
+        if self.player1_turn {
+            self.player1.walls -= 1;
+        }
+
+        else {
+            self.player2.walls -= 1;
+        }
+    }
+
+    // from the start of `self.handicap`
+    fn restart(&mut self, seats: (Seat, Seat)) {
+        self.cancel_agents();
+
+        let start = self.handicap.position();
+
+        self.player1 = Player::starting_at(true, start.player1, start.walls.0);
+        self.player2 = Player::starting_at(false, start.player2, start.walls.1);
         self.clock = 0.0;
-        self.vertical_walls = vec![vec![false; 9]; 10];
-        self.horizontal_walls = vec![vec![false; 9]; 10];
-        self.cross_walls = vec![vec![false; 10]; 10];
-        self.player1_turn = true;
-        self.played_by_cpu = played_by_cpu;
-        self.last_turn_data = GameSaveData::dummy();
+        self.vertical_walls = start.vertical_walls.iter().map(|column| column.to_vec()).collect();
+        self.horizontal_walls = start.horizontal_walls.iter().map(|row| row.to_vec()).collect();
+        self.cross_walls = start.cross_walls.iter().map(|column| column.to_vec()).collect();
+        self.player1_turn = start.player1_turn;
+        self.seats = seats;
+        self.spectator = None;
+        self.history = vec![];
+        self.moves = vec![];
+        self.explanation = None;
+        self.cancel_review();
+        self.summary = None;
+        self.setup = None;
+        self.series = None;
+        self.outcome = None;
+        self.draw_offer = None;
+        self.draw_answer = None;
+        self.position_hashes = vec![start.hash()];
+        self.forced_win = None;
 
         self.state = GameState::Playing;
         self.last_state = GameState::Playing;
@@ -218,8 +506,188 @@ impl Game {
     }
 
     fn is_human_turn(&self) -> bool {
-        self.player1_turn && !self.played_by_cpu.0
-        || !self.player1_turn && !self.played_by_cpu.1
+        self.player1_turn && self.seats.0.is_human()
+        || !self.player1_turn && self.seats.1.is_human()
+    }
+
+    // the built-in cpu, with the book and the tuned weights
+    fn cpu_seat(&self) -> Seat {
+        let config = SearchConfig {
+            threads: ai::thread_count(),
+            max_depth: MAX_DEPTH,
+            time_limit: None,
+            weights: self.weights.clone(),
+        };
+
+        let mut agent = AlphaBetaAgent::new(config)
+            .with_book(self.book.clone(), unsafe { GLOBAL_ENV.randomize_book })
+            .with_personality(self.personality);
+        let strength = if self.adaptive { Some(Strength::for_rating(self.rating.value)) } else { None };
+
+        if let Some(strength) = strength {
+            agent = agent.with_strength(strength);
+        }
+
+        let mut seat = Seat::agent(Box::new(agent));
+        seat.personality = Some(self.personality);
+        seat.strength = strength;
+
+        seat
+    }
+
+    fn toggle_paths(&mut self) {
+        self.show_paths = !self.show_paths;
+        self.buttons[6] = Button::new(0.0, 0.0, &paths_label(self.show_paths));
+        self.locate_buttons();
+    }
+
+    fn toggle_heatmap(&mut self) {
+        self.show_heatmap = !self.show_heatmap;
+        self.buttons[7] = Button::new(0.0, 0.0, &heatmap_label(self.show_heatmap));
+        self.locate_buttons();
+    }
+
+    // only the games between a human and the adaptive cpu count
+    fn update_rating(&mut self) {
+        let (human, cpu) = if self.seats.0.is_human() { (&self.seats.0, &self.seats.1) } else { (&self.seats.1, &self.seats.0) };
+        let opponent = match cpu.strength {
+            Some(strength) if human.is_human() => strength,
+            _ => { return; },
+        };
+
+        // the cpu is as strong as its level, plus what the handicap is worth to it
+        let advantage = if self.seats.0.is_human() { -self.handicap.advantage() } else { self.handicap.advantage() };
+        let score = match self.winner() {
+            Some(player1_won) if player1_won == self.seats.0.is_human() => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        };
+        self.rating.update(opponent.rating() + advantage, score);
+
+        // it's okay to lose the rating
+        let _ = self.rating.save(RATING_PATH);
+    }
+
+    // the record of every player with a profile, against the other player
+    fn update_profiles(&mut self) {
+        let winner = self.winner();
+        let walls = self.walls_placed();
+
+        for (player1, seat, other) in [(true, &self.seats.0, &self.seats.1), (false, &self.seats.1, &self.seats.0)] {
+            let profile = match seat.profile {
+                Some(index) => &mut self.profiles[index],
+                None => { continue; },
+            };
+            let opponent = if other.is_human() { "human" } else { other.name.as_str() };
+
+            profile.add_game(opponent, winner.map(|winner| winner == player1), self.moves.len(), if player1 { walls.0 } else { walls.1 });
+
+            // it's okay to lose the record
+            let _ = profile.save();
+        }
+    }
+
+    // (player1, player2)
+    fn walls_placed(&self) -> (usize, usize) {
+        let mut result = (0, 0);
+
+        for (mv, before) in self.moves.iter().zip(self.history.iter()) {
+            if !matches!(mv, Move::Step(_, _)) {
+                if before.player1_turn { result.0 += 1; } else { result.1 += 1; }
+            }
+        }
+
+        result
+    }
+
+    // the buttons on the right and the hotkeys, the same in every state
+    fn update_buttons(&mut self, inputs: &Inputs, mouse_pos: (f32, f32)) {
+        for button in self.buttons.iter_mut() {
+            button.check_mouse(mouse_pos);
+        }
+
+        if inputs.key_pressed[KEY_E] {
+            self.show_explanation = !self.show_explanation;
+        }
+
+        if inputs.key_pressed[KEY_P] {
+            self.toggle_paths();
+        }
+
+        if inputs.key_pressed[KEY_H] {
+            self.toggle_heatmap();
+        }
+
+        if !inputs.mouse_pressed[0] {
+            return;
+        }
+
+        if self.buttons[0].check_mouse(mouse_pos) {
+            self.open_setup(true);
+        }
+
+        else if self.buttons[1].check_mouse(mouse_pos) {
+            self.open_setup(false);
+        }
+
+        else if self.buttons[2].check_mouse(mouse_pos) {
+            self.curr_popup = Popup::new("Restart");
+            self.spectate();
+        }
+
+        else if self.buttons[3].check_mouse(mouse_pos) {
+            self.curr_popup = Popup::new("Undo");
+            self.undo_turn();
+        }
+
+        else if self.buttons[4].check_mouse(mouse_pos) {
+            // the game goes on under the question
+            if self.state == GameState::Playing && self.offering_player().is_some() {
+                global_env().push_context(Box::new(Confirm::new("Resign this game?", Confirmed::Resign)));
+            }
+
+            else if self.state == GameState::Playing {
+                self.resign();
+            }
+        }
+
+        else if self.buttons[5].check_mouse(mouse_pos) {
+            if self.state == GameState::Playing {
+                self.offer_draw();
+            }
+        }
+
+        else if self.buttons[6].check_mouse(mouse_pos) {
+            self.toggle_paths();
+        }
+
+        else if self.buttons[7].check_mouse(mouse_pos) {
+            self.toggle_heatmap();
+        }
+
+        else if self.buttons[8].check_mouse(mouse_pos) {
+            self.open_menu = true;
+        }
+    }
+
+    fn to_record(&self) -> GameRecord {
+        let mut record = GameRecord::new();
+        record.moves = self.moves.clone();
+        record.winner = self.winner();
+        record.reason = self.outcome.map(|(_, reason)| reason);
+        record.handicap = self.handicap.clone();
+        record.players = (self.seats.0.name.clone(), self.seats.1.name.clone());
+        record.personalities = (self.seats.0.personality, self.seats.1.personality);
+        record.time_control = self.time_control;
+        record.move_limit = self.move_limit;
+        record.times = self.move_times();
+
+        if let Some(review) = self.review_result() {
+            record.annotations = review.annotations.clone();
+            record.scores = review.scores.clone();
+        }
+
+        record
     }
 
     fn next_turn(&mut self) {
@@ -235,58 +703,37 @@ impl Game {
     }
 
     fn scale_mouse(&self, mouse_pos: (f32, f32)) -> (f32, f32) {
-        match &self.screen_scale {
-            None => mouse_pos,
-            Some(s) => ((mouse_pos.0 - s.offset_x) / s.zoom, (mouse_pos.1 - s.offset_y) / s.zoom),
-        }
+        screen::scale_mouse(&self.screen_scale, mouse_pos)
     }
 
     fn scale_screen(&self, graphics: Vec<Graphic>) -> Vec<Graphic> {
-        match &self.screen_scale {
-            None => graphics,
-            Some(s) => {
-                graphics.iter().map(
-                    |graphic|
-                    graphic.scale(0.0, 0.0, s.zoom, s.zoom).move_rel(s.offset_x, s.offset_y)
-                ).collect()
-            },
-        }
+        screen::scale_screen(&self.screen_scale, graphics)
     }
 
     fn calc_screen_scale(&mut self) {
-        let (screen_w, screen_h) = unsafe {GLOBAL_ENV.screen_size};
+        self.screen_scale = ScreenScale::calc();
+    }
 
-        if screen_w >= 760.0 && screen_h >= 1160.0 {
-            self.screen_scale = None;
-        }
+    fn get_screen_size(&self) -> (f32, f32) {
+        screen::screen_size(&self.screen_scale)
+    }
 
-        else {
-            let vert_zoom = screen_h / 800.0;
-            let horiz_zoom = screen_w / 1536.0;
-            let zoom = vert_zoom.min(horiz_zoom);
+    // the game hands the screen over to the main menu, which keeps the game to resume it
+    fn next_context(mut self: Box<Self>, graphics: Vec<Graphic>) -> (Box<dyn Context>, Vec<Graphic>, Vec<SoundAction>) {
+        if self.open_menu {
+            self.open_menu = false;
 
-            let (offset_x, offset_y) = if vert_zoom > horiz_zoom {
-                (0.0, (screen_h - 800.0 * zoom) / 2.0)
+            // an unfinished game is picked up with "Load", even after the window is closed
+            if self.is_in_progress() {
+                if let Err(e) = self.save_game() {
+                    self.curr_popup = Popup::new(&e);
+                }
             }
 
-            else {
-                ((screen_w - 1536.0 * zoom) / 2.0, 0.0)
-            };
-
-            self.screen_scale = Some(ScreenScale {
-                offset_x, offset_y, zoom
-            });
+            return transition::fade(Box::new(MainMenu::new(self)), graphics);
         }
-    }
 
-    fn get_screen_size(&self) -> (f32, f32) {
-        if self.screen_scale.is_none() {
-            unsafe { GLOBAL_ENV.screen_size }
-        }
-
-        else {
-            (1536.0, 800.0)
-        }
+        (self, graphics, vec![])
     }
 }
 
@@ -313,6 +760,12 @@ impl Context for Game {
             self.last_clock_tick = time::Instant::now();
         }
 
+        // the game is frozen under the pause menu, until it's uncovered
+        if inputs.key_pressed[KEY_ESCAPE] && self.state != GameState::ScreenTooSmall {
+            let record = if self.is_in_progress() { Some(self.to_record()) } else { None };
+            global_env().push_context(Box::new(PauseMenu::new(record, self.settings())));
+        }
+
         match self.state {
             GameState::ScreenTooSmall => {
                 let mut textbox = TextBox::new(
@@ -323,35 +776,24 @@ impl Context for Game {
                 .to_owned();
                 graphics = textbox.render();
 
-                (self, graphics, vec![])
+                self.next_context(graphics)
             },
             GameState::GameOver => {
-                for button in self.buttons.iter_mut() {
-                    button.check_mouse(mouse_pos);
-                }
+                self.update_review();
 
-                if inputs.mouse_pressed[0] {
-                    if self.buttons[0].check_mouse(mouse_pos) {
-                        self.curr_popup = Popup::new("CPU game is not implemented yet!");
-                        self.restart((false, true));
-                    }
+                self.update_spectator(&inputs, mouse_pos);
 
-                    else if self.buttons[1].check_mouse(mouse_pos) {
-                        self.curr_popup = Popup::new("Restart");
-                        self.restart((false, false));
-                    }
+                self.update_buttons(&inputs, mouse_pos);
 
-                    else if self.buttons[2].check_mouse(mouse_pos) {
-                        self.curr_popup = Popup::new("Undo");
-                        self.undo();
-                    }
+                let (box_x, box_y) = ((screen_w - BOARD_SIZE) / 2.0, (screen_h - BOARD_SIZE) / 1.2);
 
-                    else if self.buttons[3].check_mouse(mouse_pos) {
-                        unsafe { GLOBAL_ENV.quit() }
-                    }
+                if self.setup.is_some() {
+                    self.update_setup(&inputs, mouse_pos, box_x, box_y);
                 }
 
-                let (box_x, box_y) = ((screen_w - BOARD_SIZE) / 2.0, (screen_h - BOARD_SIZE) / 1.2);
+                else {
+                    self.update_summary(&inputs, mouse_pos, box_x, box_y);
+                }
 
                 let win_message_color = Color::new(
                     (((self.frame_count as f32 / 6.0).cos() + 2.0) * 48.0 + 64.0).floor() as u8,
@@ -359,36 +801,38 @@ impl Context for Game {
                     192,
                     (((self.frame_count as f32 / 8.0).cos() + 2.0) * 48.0 + 64.0).floor() as u8,
                 );
-                let win_message = TextBox::new(
-                    &format!("Player {} made it!", if self.did_player1_win() { 1 } else { 2 }),
-                    0.0, 0.0, screen_w, screen_h, 48.0,
-                ).set_color(win_message_color)
-                .align_center().render();
+                let win_message = if self.setup.is_some() { vec![] } else { self.draw_summary(box_x, box_y, win_message_color) };
 
                 graphics = vec![
                     self.draw_board(box_x, box_y),
+                    self.draw_review(),
+                    self.vis_paths(box_x, box_y),
+                    self.draw_heatmap(box_x, box_y),
                     self.draw_ui(box_x, box_y),
                     self.player1.show_trace(box_x, box_y),
                     self.player2.show_trace(box_x, box_y),
                     win_message,
+                    self.draw_setup(box_x, box_y),
                     self.curr_popup.render(),
                 ].concat();
 
                 graphics = self.scale_screen(graphics);
 
-                (self, graphics, vec![])
+                self.next_context(graphics)
             },
             GameState::Playing => {
                 let clock_check = time::Instant::now().duration_since(self.last_clock_tick.clone()).as_millis();
 
                 if clock_check > 100 {
-                    self.clock += clock_check as f32 / 1000.0;
+                    // nobody is playing while the window is in the background
+                    if !self.is_paused() && inputs.is_focused {
+                        self.clock += clock_check as f32 / 1000.0;
+                    }
+
                     self.last_clock_tick = time::Instant::now();
                 }
 
-                for button in self.buttons.iter_mut() {
-                    button.check_mouse(mouse_pos);
-                }
+                self.update_spectator(&inputs, mouse_pos);
 
                 let (box_x, box_y) = ((screen_w - BOARD_SIZE) / 2.0, (screen_h - BOARD_SIZE) / 1.2);
 
@@ -397,203 +841,99 @@ impl Context for Game {
 
                 let board_graphics = self.draw_board(box_x, box_y);
 
-                match mouse_index {
-                    Index::Box(x, y) => {
-                        self.mouse_traces.add(box_x + (x * 72) as f32 + 21.0, box_y + (y * 72) as f32 + 21.0, 48.0, 48.0);
-                    },
-                    Index::Vertical(x, y) if x < 9 && y < 8 && x > 0 => {
-                        self.mouse_traces.add(box_x + (x * 72) as f32 + 3.0, box_y + (y * 72) as f32 + 18.0, 12.0, 126.0);
-                    },
-                    Index::Horizontal(x, y) if y < 9 && x < 8 && y > 0 => {
-                        self.mouse_traces.add(box_x + (x * 72) as f32 + 18.0, box_y + (y * 72) as f32 + 3.0, 126.0, 12.0);
-                    },
-                    _ => {},
-                }
-
-                if self.is_human_turn() {
-                    if inputs.mouse_pressed[0] {
-                        let mut new_wall_placed = false;
-
-                        match mouse_index {
-                            Index::Box(x, y) => {
-                                let (x, y) = (x as i32, y as i32);
-                                let mut is_invalid_move = true;
-
-                                for next_move in self.get_valid_moves() {
-                                    if (x, y) == next_move {
-                                        self.last_turn_data = GameSaveData::from_game(&self);
-
-                                        if self.player1_turn {
-                                            self.player1.move_to(x, y);
-                                        }
+                add_mouse_trace(&mut self.mouse_traces, &mouse_index, box_x, box_y);
 
-                                        else {
-                                            self.player2.move_to(x, y);
-                                        }
+                if self.is_human_turn() && self.setup.is_none() && inputs.mouse_pressed[0] {
+                    match mouse_index {
+                        Index::Box(x, y) => {
+                            let (x, y) = (x as i32, y as i32);
+                            let mut is_invalid_move = true;
 
-                                        self.next_turn();
-                                        is_invalid_move = false;
-                                        break;
-                                    }
+                            for next_move in self.get_valid_moves() {
+                                if (x, y) == next_move {
+                                    self.play_move(Move::Step(x, y));
+                                    is_invalid_move = false;
+                                    break;
                                 }
+                            }
 
-                                if is_invalid_move {
-                                    self.curr_popup = Popup::new("Invalid Move!");
-                                }
-                            },
-                            Index::Vertical(x, y) => {
-                                if (self.player1_turn && self.player1.walls == 0) || (!self.player1_turn && self.player2.walls == 0) {
-                                    self.curr_popup = Popup::new("No walls to place!");
-                                }
-
-                                else if self.is_vertical_wall_ok_at(x, y) {
-                                    self.last_turn_data = GameSaveData::from_game(&self);
-
-                                    self.vertical_walls[x][y] = true;
-                                    self.vertical_walls[x][y + 1] = true;
-                                    self.cross_walls[x][y + 1] = true;
-
-                                    if self.player1_turn {
-                                        self.player1.walls -= 1;
-                                    }
-
-                                    else {
-                                        self.player2.walls -= 1;
-                                    }
-
-                                    new_wall_placed = true;
-                                    self.next_turn();
-                                }
-
-                                else {
-                                    self.curr_popup = Popup::new("Cannot place a wall there!");
-                                }
-                            },
-                            Index::Horizontal(x, y) => {
-                                if (self.player1_turn && self.player1.walls == 0) || (!self.player1_turn && self.player2.walls == 0) {
-                                    self.curr_popup = Popup::new("No walls to place!");
-                                }
-
-                                else if self.is_horizontal_wall_ok_at(x, y) {
-                                    self.last_turn_data = GameSaveData::from_game(&self);
-
-                                    self.horizontal_walls[y][x] = true;
-                                    self.horizontal_walls[y][x + 1] = true;
-                                    self.cross_walls[x + 1][y] = true;
-
-                                    if self.player1_turn {
-                                        self.player1.walls -= 1;
-                                    }
-
-                                    else {
-                                        self.player2.walls -= 1;
-                                    }
-
-                                    new_wall_placed = true;
-                                    self.next_turn();
-                                }
-
-                                else {
-                                    self.curr_popup = Popup::new("Cannot place a wall there!");
-                                }
-                            },
-                            Index::None => {},
-                        }
-
-                        if new_wall_placed {
-                            let visited = vec![vec![false; 10]; 10];
-
-                            if !self.dfs(self.player1.position, 8, 1, &mut visited.clone()) || !self.dfs(self.player2.position, 0, -1, &mut visited.clone()) {
-                                self.curr_popup = Popup::new("You may not trap a player!");
-                                self.undo();
+                            if is_invalid_move {
+                                self.curr_popup = Popup::new("Invalid Move!");
                             }
-                        }
+                        },
+                        Index::Vertical(x, y) => { self.place_wall(Move::Vertical(x, y)); },
+                        Index::Horizontal(x, y) => { self.place_wall(Move::Horizontal(x, y)); },
+                        Index::None => {},
                     }
                 }
 
-                else {
-                    // TODO: impl ai mode
-                    self.curr_popup = Popup::new("CPU game is not implemented yet!");
-                    self.restart((false, false));
-                }
-
-                if inputs.mouse_pressed[0] {
-                    if self.buttons[0].check_mouse(mouse_pos) {
-                        self.curr_popup = Popup::new("CPU game is not implemented yet!");
-                        self.restart((false, true));
-                    }
+                self.update_agents();
 
-                    else if self.buttons[1].check_mouse(mouse_pos) {
-                        self.curr_popup = Popup::new("Restart");
-                        self.restart((false, false));
-                    }
+                self.update_buttons(&inputs, mouse_pos);
+                self.update_setup(&inputs, mouse_pos, box_x, box_y);
 
-                    else if self.buttons[2].check_mouse(mouse_pos) {
-                        self.curr_popup = Popup::new("Undo");
-                        self.undo();
-                    }
-
-                    else if self.buttons[3].check_mouse(mouse_pos) {
-                        unsafe { GLOBAL_ENV.quit() }
-                    }
-                }
-
-                if self.did_player1_win() {
-                    self.curr_popup = Popup::new("Player 1 Won!");
-                    self.state = GameState::GameOver;
-                    self.last_state = GameState::GameOver;
-                }
-
-                else if self.did_player2_win() {
-                    self.curr_popup = Popup::new("Player 2 Won!");
-                    self.state = GameState::GameOver;
-                    self.last_state = GameState::GameOver;
-                }
+                self.check_ending();
 
                 graphics = vec![
                     board_graphics,
+                    self.draw_explanation(box_x, box_y),
+                    self.vis_paths(box_x, box_y),
+                    self.draw_heatmap(box_x, box_y),
                     self.draw_player(box_x, box_y),
                     self.draw_ui(box_x, box_y),
                     self.mouse_traces.render(),
+                    self.draw_setup(box_x, box_y),
                     self.curr_popup.render(),
                 ].concat();
 
                 graphics = self.scale_screen(graphics);
 
-                (self, graphics, vec![])
+                self.next_context(graphics)
             }
         }
     }
-}
 
-fn get_cursor_index(mouse_x: f32, mouse_y: f32, box_x: f32, box_y: f32) -> Index {
-    if mouse_x <= box_x || mouse_x >= box_x + BOARD_SIZE || mouse_y <= box_y || mouse_y >= box_y + BOARD_SIZE {
-        Index::None
-    }
-
-    else {
-        let (mouse_index_x, mouse_index_y) = ((mouse_x - box_x) as usize / 72, (mouse_y - box_y) as usize / 72);
-        let (mouse_rem_x, mouse_rem_y) = ((mouse_x - box_x) as usize % 72, (mouse_y - box_y) as usize % 72);
+    fn uncover(&mut self, result: Option<Box<dyn Any>>) {
+        let confirmed = result.as_ref().and_then(|result| result.downcast_ref::<Confirmed>());
 
-        if mouse_rem_x > 18 && mouse_rem_y > 18 {
-            Index::Box(mouse_index_x, mouse_index_y)
+        // the game may have ended under the question
+        if confirmed == Some(&Confirmed::Resign) && self.state == GameState::Playing {
+            self.resign();
         }
 
-        else if mouse_rem_y <= 18 {
-            Index::Horizontal(mouse_index_x, mouse_index_y)
-        }
+        // the time in the pause menu does not count on the clock
+        if let Some(unpaused) = result.as_ref().and_then(|result| result.downcast_ref::<Unpaused>()) {
+            if let Some(settings) = &unpaused.settings {
+                self.apply_settings(settings);
+            }
 
-        else {
-            Index::Vertical(mouse_index_x, mouse_index_y)
+            self.open_menu = unpaused.to_menu;
+            self.resume();
         }
     }
 }
 
-enum Index {
-    None,
-    Box(usize, usize),
-    Horizontal(usize, usize),
-    Vertical(usize, usize),
+pub fn personality_label(personality: Personality) -> String {
+    format!("CPU: {}", personality.name())
+}
+
+fn paths_label(show_paths: bool) -> String {
+    format!("Paths: {}", if show_paths { "on" } else { "off" })
+}
+
+fn heatmap_label(show_heatmap: bool) -> String {
+    format!("Heatmap: {}", if show_heatmap { "on" } else { "off" })
+}
+
+pub fn level_label(adaptive: bool) -> String {
+    format!("Level: {}", if adaptive { "adaptive" } else { "full" })
+}
+
+pub fn time_control_label(time_control: Option<usize>) -> String {
+    match time_control {
+        Some(seconds) => format!("Time: {} min", seconds / 60),
+        None => String::from("Time: none"),
+    }
 }
 
-const BOARD_SIZE: f32 = 666.0;
+const LAST_GAME_PATH: &str = "last_game.txt";
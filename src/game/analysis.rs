@@ -0,0 +1,26 @@
+use super::Game;
+use crate::ai::endgame::{Outcome, Solver};
+use crate::position::Position;
+use std::time::Duration;
+
+impl Game {
+    // it runs on every move, on the thread that draws the frames, so it gives up early
+    pub(super) fn update_analysis(&mut self) {
+        let position = Position::from_game(self);
+
+        self.forced_win = match Solver::new(ANALYSIS_BUDGET).with_time_limit(ANALYSIS_TIME_LIMIT).solve(&position) {
+            Some((Outcome::Win(plies), _)) if plies > 0 => Some((position.player1_turn, Outcome::Win(plies).moves_to_end())),
+            Some((Outcome::Loss(plies), _)) if plies > 0 => Some((!position.player1_turn, Outcome::Loss(plies).moves_to_end())),
+            _ => None,
+        };
+    }
+
+    pub(super) fn forced_win_message(&self) -> Option<String> {
+        self.forced_win.map(
+            |(player1, moves)| format!("Player {}: forced win in {}", if player1 { 1 } else { 2 }, moves)
+        )
+    }
+}
+
+const ANALYSIS_BUDGET: usize = 4000;
+const ANALYSIS_TIME_LIMIT: Duration = Duration::from_millis(5);
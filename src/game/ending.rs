@@ -0,0 +1,238 @@
+use super::{Game, GameState, LAST_GAME_PATH};
+use crate::ai::search::{search, SearchConfig};
+use crate::ai::tt::TranspositionTable;
+use crate::popup::Popup;
+use crate::position::Position;
+use crate::record::EndReason;
+use std::sync::atomic::AtomicBool;
+use std::thread;
+use std::time::Duration;
+
+// The ways a game ends: a player reaches the goal or resigns, the players agree to a draw,
+// the same position shows up for the third time, the game runs out of moves, or a player runs out of time.
+impl Game {
+    // `Some(true)` if player 1 won, `None` for a draw or a game that is not over
+    pub(super) fn winner(&self) -> Option<bool> {
+        self.outcome.and_then(|(winner, _)| winner)
+    }
+
+    // after every frame of `GameState::Playing`
+    pub(super) fn check_ending(&mut self) {
+        if self.outcome.is_some() {
+            return;
+        }
+
+        if self.update_draw_answer() {
+            self.end_game(None, EndReason::Agreement);
+        }
+
+        else if self.did_player1_win() {
+            self.end_game(Some(true), EndReason::Goal);
+        }
+
+        else if self.did_player2_win() {
+            self.end_game(Some(false), EndReason::Goal);
+        }
+
+        else if self.repetitions() >= REPETITION_LIMIT {
+            self.end_game(None, EndReason::Repetition);
+        }
+
+        else if self.move_limit.map(|limit| self.moves.len() >= limit).unwrap_or(false) {
+            self.end_game(None, EndReason::MoveLimit);
+        }
+
+        else if self.time_left(self.player1_turn).map(|left| left <= 0.0).unwrap_or(false) {
+            self.end_game(Some(!self.player1_turn), EndReason::Time);
+        }
+    }
+
+    pub(super) fn end_game(&mut self, winner: Option<bool>, reason: EndReason) {
+        self.outcome = Some((winner, reason));
+        self.draw_offer = None;
+        self.curr_popup = Popup::new(&self.ending_message());
+        self.state = GameState::GameOver;
+        self.last_state = GameState::GameOver;
+
+        self.cancel_agents();
+        self.start_review();
+        self.count_series_game();
+        self.open_summary();
+
+        // it's okay to lose the record
+        let _ = self.to_record().save(LAST_GAME_PATH);
+        self.update_rating();
+        self.update_profiles();
+    }
+
+    pub(super) fn ending_message(&self) -> String {
+        let player = |player1: bool| if player1 { 1 } else { 2 };
+
+        match self.outcome {
+            Some((Some(winner), EndReason::Goal)) => format!("Player {} made it!", player(winner)),
+            Some((Some(winner), EndReason::Time)) => format!("Player {} ran out of time", player(!winner)),
+            Some((Some(winner), _)) => format!("Player {} resigned", player(!winner)),
+            Some((None, EndReason::Repetition)) => String::from("Draw by repetition"),
+            Some((None, EndReason::MoveLimit)) => String::from("Draw by the move limit"),
+            Some((None, _)) => String::from("Draw agreed"),
+            None => String::new(),
+        }
+    }
+
+    // the seconds spent on each move of the game
+    pub(super) fn move_times(&self) -> Vec<f32> {
+        let mut last = 0.0;
+
+        self.history.iter().map(|before| {
+            let time = before.clock - last;
+            last = before.clock;
+            time
+        }).collect()
+    }
+
+    // `None` without a time control
+    pub(super) fn time_left(&self, player1: bool) -> Option<f32> {
+        let time_control = self.time_control? as f32;
+        let turn_start = self.history.last().map(|before| before.clock).unwrap_or(0.0);
+        let mut used = if self.player1_turn == player1 { self.clock - turn_start } else { 0.0 };
+
+        for (before, time) in self.history.iter().zip(self.move_times()) {
+            if before.player1_turn == player1 {
+                used += time;
+            }
+        }
+
+        Some((time_control - used).max(0.0))
+    }
+
+    // how many times the current position was on the board, with the same walls and the same player to move
+    fn repetitions(&self) -> usize {
+        let hash = match self.position_hashes.last() {
+            Some(hash) => *hash,
+            None => { return 0; },
+        };
+
+        self.position_hashes.iter().filter(|h| **h == hash).count()
+    }
+
+    // Against the cpu, the human resigns. Between humans, the player to move does.
+    pub(super) fn resign(&mut self) {
+        let player1 = match self.offering_player() {
+            Some(player1) => player1,
+            None => {
+                self.curr_popup = Popup::new("Only a human can resign");
+                return;
+            },
+        };
+
+        self.end_game(Some(!player1), EndReason::Resignation);
+    }
+
+    // The cpu accepts if it does not think that it's winning, after a short search in the background.
+    // A human accepts by offering a draw back before making a move.
+    pub(super) fn offer_draw(&mut self) {
+        let player1 = match self.offering_player() {
+            Some(player1) => player1,
+            None => {
+                self.curr_popup = Popup::new("Only a human can offer a draw");
+                return;
+            },
+        };
+
+        let opponent = if player1 { &self.seats.1 } else { &self.seats.0 };
+
+        if !opponent.is_human() {
+            if self.draw_answer.is_some() {
+                return;
+            }
+
+            match self.cpu_accepts_draw(!player1) {
+                Some(true) => { self.end_game(None, EndReason::Agreement); },
+                Some(false) => { self.curr_popup = Popup::new("The CPU declined the draw"); },
+                None => { self.curr_popup = Popup::new("The CPU thinks about the draw"); },
+            }
+        }
+
+        else if self.draw_offer == Some(!player1) {
+            self.end_game(None, EndReason::Agreement);
+        }
+
+        else {
+            self.draw_offer = Some(player1);
+            self.curr_popup = Popup::new(&format!("Player {} offers a draw", if player1 { 1 } else { 2 }));
+        }
+    }
+
+    // the human who resigns or offers a draw: `Some(true)` for player 1
+    pub(super) fn offering_player(&self) -> Option<bool> {
+        match (self.seats.0.is_human(), self.seats.1.is_human()) {
+            (true, true) => Some(self.player1_turn),
+            (true, false) => Some(true),
+            (false, true) => Some(false),
+            (false, false) => None,
+        }
+    }
+
+    // a short search from the cpu's view, unless the endgame solver already knows the result
+    // `None` while the search runs, and `update_draw_answer` gets its answer
+    fn cpu_accepts_draw(&mut self, cpu_player1: bool) -> Option<bool> {
+        if let Some((player1, _)) = self.forced_win {
+            return Some(player1 != cpu_player1);
+        }
+
+        let position = Position::from_game(self);
+        let config = SearchConfig {
+            threads: 1,
+            max_depth: DRAW_OFFER_DEPTH,
+            time_limit: Some(DRAW_OFFER_TIME_LIMIT),
+            weights: self.weights.clone(),
+        };
+        let hash = position.hash();
+        let handle = thread::spawn(move || {
+            let result = search(&position, &config, &TranspositionTable::new(DRAW_OFFER_TT_BITS), &AtomicBool::new(false));
+            let score = if position.player1_turn == cpu_player1 { result.score } else { -result.score };
+
+            score <= DRAW_ACCEPT_SCORE
+        });
+
+        self.draw_answer = Some((hash, handle));
+        None
+    }
+
+    // whether the cpu accepted the draw, once its search is done
+    // the answer is thrown away if the position has changed since the offer
+    fn update_draw_answer(&mut self) -> bool {
+        let (hash, handle) = match self.draw_answer.take() {
+            Some((hash, handle)) if handle.is_finished() => (hash, handle),
+            answer => {
+                self.draw_answer = answer;
+                return false;
+            },
+        };
+
+        let accepted = handle.join().unwrap_or(false);
+
+        if Position::from_game(self).hash() != hash {
+            return false;
+        }
+
+        if !accepted {
+            self.curr_popup = Popup::new("The CPU declined the draw");
+        }
+
+        accepted
+    }
+}
+
+const REPETITION_LIMIT: usize = 3;
+const DRAW_OFFER_DEPTH: u8 = 3;
+const DRAW_OFFER_TIME_LIMIT: Duration = Duration::from_millis(300);
+const DRAW_OFFER_TT_BITS: u32 = 16;
+
+// the cpu takes a draw unless it's ahead by more than this
+const DRAW_ACCEPT_SCORE: i32 = 20;
+
+pub const MOVE_LIMITS: [Option<usize>; 4] = [None, Some(100), Some(150), Some(200)];
+
+// seconds for each player
+pub const TIME_CONTROLS: [Option<usize>; 4] = [None, Some(180), Some(300), Some(600)];
@@ -0,0 +1,90 @@
+use super::Game;
+use crate::dfs::draw_cells;
+use crate::engine::color::Color;
+use crate::engine::graphic::Graphic;
+use crate::engine::widget::textbox::TextBox;
+use crate::position::{Move, Position};
+
+// Why the cpu played its last move: how the move changed both shortest paths, and what it expects next.
+// The new paths are drawn on the board.
+pub struct Explanation {
+    player1: bool,  // whether player 1 moved
+    name: String,
+    mv: Move,
+    before: (u8, u8),  // path lengths of (player1, player2)
+    after: (u8, u8),
+    pv: Vec<Move>,
+    path1: Vec<(i32, i32)>,  // after the move
+    path2: Vec<(i32, i32)>,
+}
+
+impl Game {
+    // `before` is the position that `mv` was played on
+    pub(super) fn explain_move(&mut self, before: &Position, mv: Move, pv: Vec<Move>, name: String) {
+        let after = Position::from_game(self);
+        let player1 = before.player1_turn;
+
+        let lengths = |position: &Position| Some((position.distance(true)?, position.distance(false)?));
+        let paths = after.shortest_path(true).zip(after.shortest_path(false));
+
+        self.explanation = match (lengths(before), lengths(&after), paths) {
+            (Some(before), Some(after), Some((path1, path2))) => Some(Explanation {
+                player1, name, mv, before, after, pv, path1, path2,
+            }),
+
+            // unreachable with legal moves
+            _ => None,
+        };
+    }
+
+    pub(super) fn draw_explanation(&self, box_x: f32, box_y: f32) -> Vec<Graphic> {
+        let explanation = match &self.explanation {
+            Some(explanation) if self.show_explanation => explanation,
+            _ => { return vec![]; },
+        };
+
+        let mover = if explanation.player1 { 1 } else { 2 };
+        let mut lines = vec![format!("p{} ({}) played {}", mover, explanation.name, explanation.mv.to_notation())];
+
+        for (player, before, after) in [(1, explanation.before.0, explanation.after.0), (2, explanation.before.1, explanation.after.1)] {
+            let mut line = format!("p{} path: {} -> {}", player, before, after);
+
+            if player != mover && after > before && !matches!(explanation.mv, Move::Step(_, _)) {
+                line = format!("{} (+{} from the wall)", line, after - before);
+            }
+
+            lines.push(line);
+        }
+
+        if explanation.pv.len() > 1 {
+            let expected: Vec<String> = explanation.pv.iter().skip(1).take(MAX_PV_LENGTH).map(|mv| mv.to_notation()).collect();
+            lines.push(format!("expects: {}", expected.join(" ")));
+        }
+
+        lines.push(String::from("(e: hide)"));
+
+        // the path overlay already shows the same paths
+        let paths = if self.show_paths {
+            vec![]
+        } else {
+            [
+                draw_cells(&explanation.path1, Color::player1_trans(), box_x, box_y),
+                draw_cells(&explanation.path2, Color::player2_trans(), box_x, box_y),
+            ].concat()
+        };
+
+        [
+            paths,
+            TextBox::new(
+                &lines.join("\n"),
+                30.0,
+                400.0,
+                300.0,
+                150.0,
+                18.0,
+            ).set_color(Color::ui()).render(),
+        ].concat()
+    }
+}
+
+const MAX_PV_LENGTH: usize = 6;
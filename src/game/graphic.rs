@@ -2,17 +2,24 @@ use super::{Game, BOARD_SIZE};
 use crate::engine::graphic::Graphic;
 use crate::engine::color::Color;
 use crate::engine::widget::textbox::TextBox;
+use crate::board;
+use crate::position::Position;
 
 impl Game {
     pub fn draw_ui(&mut self, box_x: f32, box_y: f32) -> Vec<Graphic> {
         let mut result = Vec::with_capacity(1000);
+        // with a time control, the time left of (player1, player2) instead of the time of the game
+        let (timer_text, timer_size) = match (self.time_left(true), self.time_left(false)) {
+            (Some(left1), Some(left2)) => (format!("{}  {}", minutes(left1), minutes(left2)), 36.0),
+            _ => (format!("{}", self.clock as usize), 51.0),
+        };
         let timer = TextBox::new(
-            &format!("{}", self.clock as usize),
+            &timer_text,
             box_x + BOARD_SIZE / 2.0 - 105.0,
             box_y - 60.0,
             210.0,
             60.0,
-            51.0,
+            timer_size,
         ).set_color(Color::ui()).align_center().render();
 
         for i in 0..self.player1.walls {
@@ -84,7 +91,7 @@ impl Game {
         }
 
         let p1_info = TextBox::new(
-            &format!("p1 ({})", if self.played_by_cpu.0 { "cpu" } else { "human" }),
+            &self.seat_label(true),
             box_x - 90.0,
             box_y - 80.0,
             210.0,
@@ -93,7 +100,7 @@ impl Game {
         ).set_color(Color::ui()).align_center().render();
 
         let p2_info = TextBox::new(
-            &format!("p2 ({})", if self.played_by_cpu.1 { "cpu" } else { "human" }),
+            &self.seat_label(false),
             box_x + BOARD_SIZE - 120.0,
             box_y - 80.0,
             210.0,
@@ -101,6 +108,34 @@ impl Game {
             21.0,
         ).set_color(Color::ui()).align_center().render();
 
+        let avatars = self.draw_avatars(box_x, box_y);
+
+        let analysis = match self.forced_win_message() {
+            Some(message) => TextBox::new(
+                &message,
+                box_x,
+                box_y - 110.0,
+                BOARD_SIZE,
+                30.0,
+                21.0,
+            ).set_color(Color::ui()).align_center().render(),
+            None => vec![],
+        };
+
+        let (screen_w, _) = self.get_screen_size();
+        let rating = if self.adaptive {
+            TextBox::new(
+                &format!("your rating: {} ({} games)", self.rating.value, self.rating.games),
+                screen_w - 210.0,
+                30.0 + self.buttons.len() as f32 * 60.0,
+                180.0,
+                30.0,
+                18.0,
+            ).set_color(Color::ui()).align_center().render()
+        } else {
+            vec![]
+        };
+
         let mut buttons = vec![];
 
         for button in self.buttons.iter_mut() {
@@ -112,89 +147,53 @@ impl Game {
             timer,
             p1_info,
             p2_info,
+            avatars,
+            analysis,
+            rating,
             buttons.concat(),
+            self.draw_spectator(),
+            self.draw_series(),
         ].concat()
     }
 
-    pub fn draw_board(&self, box_x: f32, box_y: f32) -> Vec<Graphic> {
-        let mut board_graphics = Vec::with_capacity(100);
-
-        board_graphics.push(Graphic::new_round_rect(box_x, box_y, BOARD_SIZE, BOARD_SIZE, 32.0, 0.0, Color::board_normal()));
+    // the name of a player with a profile, or the kind of the player
+    fn seat_label(&self, player1: bool) -> String {
+        let seat = if player1 { &self.seats.0 } else { &self.seats.1 };
 
-        for x in 0..9 {
-            for y in 0..9 {
-                board_graphics.push(
-                    Graphic::new_round_rect(box_x + (x * 72) as f32 + 18.0, box_y + (y * 72) as f32 + 18.0, 54.0, 54.0, 8.0, 0.0, Color::box_normal())
-                );
-            }
+        if seat.profile.is_some() {
+            seat.name.clone()
         }
 
-        for (y, row) in self.horizontal_walls.iter().enumerate() {
-            for (x, wall) in row.iter().enumerate() {
-                if *wall {
-                    board_graphics.push(
-                        Graphic::new_rect(box_x + (x * 72) as f32 + 24.0, box_y + (y * 72) as f32 + 5.0, 42.0, 8.0, 0.0, Color::wall())
-                    );
-                }
-            }
+        else {
+            format!("p{} ({})", if player1 { 1 } else { 2 }, seat.name)
         }
+    }
 
-        for (x, column) in self.vertical_walls.iter().enumerate() {
-            for (y, wall) in column.iter().enumerate() {
-                if *wall {
-                    board_graphics.push(
-                        Graphic::new_rect(box_x + (x * 72) as f32 + 5.0, box_y + (y * 72) as f32 + 24.0, 8.0, 42.0, 0.0, Color::wall())
-                    );
-                }
-            }
-        }
+    // a dot in the avatar color under the label, for the players with a profile
+    fn draw_avatars(&self, box_x: f32, box_y: f32) -> Vec<Graphic> {
+        let mut result = vec![];
 
-        for (x, row) in self.cross_walls.iter().enumerate() {
-            for (y, wall) in row.iter().enumerate() {
-                if *wall {
-                    board_graphics.push(
-                        Graphic::new_rect(box_x + (x * 72) as f32 + 5.0, box_y + (y * 72) as f32 + 5.0, 8.0, 8.0, 0.0, Color::wall()),
-                    );
-                }
+        for (seat, x) in [(&self.seats.0, box_x + 15.0), (&self.seats.1, box_x + BOARD_SIZE - 15.0)] {
+            if let Some(index) = seat.profile {
+                result.push(Graphic::new_circle(x, box_y - 12.0, 8.0, 0.0, self.profiles[index].avatar_color()));
             }
         }
 
-        board_graphics
+        result
     }
 
-    pub fn draw_player(&self, box_x: f32, box_y: f32) -> Vec<Graphic> {
-        let mut result = vec![
-            Graphic::new_circle(
-                box_x + (self.player1.position.0 * 72) as f32 + 45.0,
-                box_y + (self.player1.position.1 * 72) as f32 + 45.0,
-                16.0, 0.0,
-                Color::player1_normal(),
-            ),
-            Graphic::new_circle(
-                box_x + (self.player2.position.0 * 72) as f32 + 45.0,
-                box_y + (self.player2.position.1 * 72) as f32 + 45.0,
-                16.0, 0.0,
-                Color::player2_normal(),
-            ),
-        ];
+    pub fn draw_board(&self, box_x: f32, box_y: f32) -> Vec<Graphic> {
+        board::draw_board(&Position::from_game(self), box_x, box_y)
+    }
 
-        let curr_color = if self.player1_turn {
-            Color::player1_trans()
-        } else {
-            Color::player2_trans()
-        };
+    pub fn draw_player(&self, box_x: f32, box_y: f32) -> Vec<Graphic> {
+        board::draw_pawns(&Position::from_game(self), box_x, box_y)
+    }
+}
 
-        for (x, y) in self.get_valid_moves() {
-            result.push(
-                Graphic::new_circle(
-                    box_x + (x * 72) as f32 + 45.0,
-                    box_y + (y * 72) as f32 + 45.0,
-                    9.0, 0.0,
-                    curr_color.clone(),
-                ),
-            );
-        }
+// `m:ss`
+fn minutes(seconds: f32) -> String {
+    let seconds = seconds.ceil() as usize;
 
-        result
-    }
+    format!("{}:{:02}", seconds / 60, seconds % 60)
 }
@@ -0,0 +1,73 @@
+use super::Game;
+use crate::engine::color::Color;
+use crate::engine::graphic::Graphic;
+use crate::position::{Move, Position};
+
+impl Game {
+    // Every legal wall slot for the player to move, with how many steps it would add to the opponent's path
+    // minus how many it would add to the player's own. It's empty if the player has no walls left.
+    pub(super) fn wall_threats(&self) -> Vec<(Move, i32)> {
+        let position = Position::from_game(self);
+        let me = position.player1_turn;
+
+        let (mine, theirs) = match (position.distance(me), position.distance(!me)) {
+            (Some(mine), Some(theirs)) if position.curr_walls() > 0 => (mine as i32, theirs as i32),
+            _ => { return vec![]; },
+        };
+
+        let mut slots = Vec::with_capacity(128);
+
+        for x in 0..9 {
+            for y in 0..9 {
+                if self.is_vertical_wall_ok_at(x, y) {
+                    slots.push(Move::Vertical(x, y));
+                }
+
+                if self.is_horizontal_wall_ok_at(x, y) {
+                    slots.push(Move::Horizontal(x, y));
+                }
+            }
+        }
+
+        let mut result = Vec::with_capacity(slots.len());
+
+        for wall in slots {
+            let mut next = position.clone();
+            next.apply(wall);
+
+            // walls that trap a player are not legal
+            if let (Some(my_distance), Some(their_distance)) = (next.distance(me), next.distance(!me)) {
+                result.push((wall, (their_distance as i32 - theirs) - (my_distance as i32 - mine)));
+            }
+        }
+
+        result
+    }
+
+    // green for the walls that hurt the opponent more, red for the ones that hurt the player to move more
+    pub(super) fn draw_heatmap(&self, box_x: f32, box_y: f32) -> Vec<Graphic> {
+        if !self.show_heatmap {
+            return vec![];
+        }
+
+        self.wall_threats().into_iter().map(
+            |(wall, gain)| {
+                let alpha = (64 + gain.unsigned_abs().min(MAX_SHOWN_GAIN) * 32).min(255) as u8;
+                let color = match gain {
+                    0 => Color::new(128, 128, 128, 64),
+                    gain if gain > 0 => Color::new(64, 192, 64, alpha),
+                    _ => Color::new(192, 64, 64, alpha),
+                };
+
+                match wall {
+                    Move::Vertical(x, y) => Graphic::new_rect(box_x + (x * 72) as f32 + 3.0, box_y + (y * 72) as f32 + 18.0, 12.0, 126.0, 0.0, color),
+                    Move::Horizontal(x, y) => Graphic::new_rect(box_x + (x * 72) as f32 + 18.0, box_y + (y * 72) as f32 + 3.0, 126.0, 12.0, 0.0, color),
+                    Move::Step(_, _) => unreachable!(),
+                }
+            }
+        ).collect()
+    }
+}
+
+// the colors stop getting stronger above this
+const MAX_SHOWN_GAIN: u32 = 6;
@@ -0,0 +1,143 @@
+use super::saved_game::SAVE_PATH;
+use crate::engine::color::Color;
+use crate::engine::context::{Context, Cover};
+use crate::engine::global::global_env;
+use crate::engine::graphic::Graphic;
+use crate::engine::inputs::Inputs;
+use crate::engine::keys::KEY_ESCAPE;
+use crate::engine::sound::SoundAction;
+use crate::engine::widget::{button::Button, textbox::TextBox};
+use crate::popup::Popup;
+use crate::record::GameRecord;
+use crate::screen::{self, ScreenScale};
+use crate::settings::{Settings, SettingsOverlay};
+use std::any::Any;
+
+// The menu that ESC opens over a game. The game is frozen under it, clock and cpus included,
+// so the record that it saves is the game as it is. It pops with `Unpaused`, for the game to pick up.
+pub struct PauseMenu {
+    record: Option<GameRecord>,  // `None` when there's nothing to save
+    settings: Settings,
+    is_changed: bool,  // the settings were opened
+    is_closed: bool,
+    buttons: Vec<Button>,  // resume, settings, save, quit to menu
+    curr_popup: Popup,
+    screen_scale: Option<ScreenScale>,
+    frame_count: usize,
+}
+
+// what the game does when the pause menu is gone
+pub(super) struct Unpaused {
+    pub settings: Option<Settings>,
+    pub to_menu: bool,
+}
+
+impl PauseMenu {
+    pub(super) fn new(record: Option<GameRecord>, settings: Settings) -> Self {
+        PauseMenu {
+            record,
+            settings,
+            is_changed: false,
+            is_closed: false,
+            buttons: ["Resume", "Settings", "Save", "Quit to menu"].iter().map(|label| Button::new(0.0, 0.0, label)).collect(),
+            curr_popup: Popup::dummy(),
+            screen_scale: ScreenScale::calc(),
+            frame_count: 0,
+        }
+    }
+
+    fn save(&mut self) {
+        let result = match &self.record {
+            Some(record) => record.save(SAVE_PATH),
+            None => Err(String::from("Nothing to save")),
+        };
+
+        self.curr_popup = match result {
+            Ok(()) => Popup::new("Game saved"),
+            Err(e) => Popup::new(&e),
+        };
+    }
+
+    // it's popped at the end of the frame, so it must not pop twice
+    fn close(&mut self, to_menu: bool) {
+        if self.is_closed {
+            return;
+        }
+
+        self.is_closed = true;
+
+        let unpaused = Unpaused {
+            settings: if self.is_changed { Some(self.settings.clone()) } else { None },
+            to_menu,
+        };
+
+        global_env().pop_context(Some(Box::new(unpaused)));
+    }
+}
+
+impl Context for PauseMenu {
+    fn frame(mut self: Box<Self>, inputs: Inputs) -> (Box<dyn Context>, Vec<Graphic>, Vec<SoundAction>) {
+        self.frame_count += 1;
+
+        if inputs.is_screen_size_changed || self.frame_count & 7 == 7 {
+            self.screen_scale = ScreenScale::calc();
+        }
+
+        let (screen_w, screen_h) = screen::screen_size(&self.screen_scale);
+        let mouse_pos = screen::scale_mouse(&self.screen_scale, inputs.mouse_pos);
+        let (panel_x, panel_y) = ((screen_w - PANEL_W) / 2.0, (screen_h - PANEL_H) / 2.0);
+        let mut clicked = None;
+
+        for (index, button) in self.buttons.iter_mut().enumerate() {
+            button.move_to(panel_x + (PANEL_W - 180.0) / 2.0, panel_y + ROW_Y + index as f32 * ROW_GAP);
+
+            if button.check_mouse(mouse_pos) && inputs.mouse_pressed[0] {
+                clicked = Some(index);
+            }
+        }
+
+        if inputs.key_pressed[KEY_ESCAPE] {
+            clicked = Some(0);
+        }
+
+        match clicked {
+            Some(0) => { self.close(false); },
+            Some(1) => {
+                global_env().push_context(Box::new(SettingsOverlay::new(self.settings.clone())));
+            },
+            Some(2) => { self.save(); },
+            Some(3) => { self.close(true); },
+            _ => {},
+        }
+
+        let graphics = [
+            vec![
+                Graphic::new_rect(0.0, 0.0, screen_w, screen_h, 0.0, Color::new(0, 0, 0, 96)),
+                Graphic::new_round_rect(panel_x, panel_y, PANEL_W, PANEL_H, 16.0, 0.0, Color::new(16, 16, 16, 224)),
+            ],
+            TextBox::new("Paused", panel_x, panel_y + 20.0, PANEL_W, 50.0, 32.0).set_color(Color::ui()).align_center().render(),
+            self.buttons.iter_mut().flat_map(|button| button.render()).collect(),
+            self.curr_popup.render(),
+        ].concat();
+
+        let graphics = screen::scale_screen(&self.screen_scale, graphics);
+
+        (self, graphics, vec![])
+    }
+
+    fn cover(&self) -> Cover {
+        Cover::Overlay { block_input: true, block_updates: true }
+    }
+
+    fn uncover(&mut self, result: Option<Box<dyn Any>>) {
+        if let Some(settings) = result.and_then(|result| result.downcast::<Settings>().ok()) {
+            self.settings = *settings;
+            self.is_changed = true;
+        }
+    }
+}
+
+const PANEL_W: f32 = 300.0;
+const PANEL_H: f32 = 330.0;
+const ROW_Y: f32 = 90.0;
+const ROW_GAP: f32 = 55.0;
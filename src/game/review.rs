@@ -0,0 +1,146 @@
+use super::{Game, LAST_GAME_PATH};
+use crate::ai::review::{review, Annotation, Review, REVIEW_DEPTH};
+use crate::engine::color::Color;
+use crate::engine::graphic::Graphic;
+use crate::engine::widget::textbox::TextBox;
+use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+use std::thread::{self, JoinHandle};
+
+// The finished game is reviewed in a background thread, and the result is shown next to the board:
+// the moves with their annotations, and player 1's evaluation over the game.
+pub enum ReviewState {
+    Running(JoinHandle<Option<Review>>, Arc<AtomicBool>),
+    Done(Review),
+}
+
+impl Game {
+    pub(super) fn start_review(&mut self) {
+        self.cancel_review();
+
+        let start = self.handicap.position();
+        let moves = self.moves.clone();
+        let weights = self.weights.clone();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_ = stop.clone();
+
+        let handle = thread::spawn(move || review(&start, &moves, REVIEW_DEPTH, &weights, &stop_));
+        self.review = Some(ReviewState::Running(handle, stop));
+    }
+
+    pub(super) fn cancel_review(&mut self) {
+        if let Some(ReviewState::Running(_, stop)) = self.review.take() {
+            stop.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub(super) fn update_review(&mut self) {
+        self.review = match self.review.take() {
+            Some(ReviewState::Running(handle, _)) if handle.is_finished() => match handle.join() {
+                Ok(Some(review)) => Some(ReviewState::Done(review)),
+                _ => None,
+            },
+            review => {
+                self.review = review;
+                return;
+            },
+        };
+
+        // the record was saved when the game ended, and now it has the annotations too
+        let _ = self.to_record().save(LAST_GAME_PATH);
+    }
+
+    pub(super) fn review_result(&self) -> Option<&Review> {
+        match &self.review {
+            Some(ReviewState::Done(review)) => Some(review),
+            _ => None,
+        }
+    }
+
+    pub(super) fn draw_review(&self) -> Vec<Graphic> {
+        let review = match &self.review {
+            Some(ReviewState::Done(review)) => review,
+            Some(ReviewState::Running(_, _)) => {
+                return TextBox::new("reviewing the game...", 30.0, 400.0, 300.0, 30.0, 18.0).set_color(Color::ui()).render();
+            },
+            None => { return vec![]; },
+        };
+
+        let mut tokens = vec![];
+
+        // player 2 may move first with a handicap, and then the numbers start with `1...`
+        let first = self.handicap.player1_first;
+        let is_player1 = |ply: usize| ply.is_multiple_of(2) == first;
+        let offset = if first { 0 } else { 1 };
+
+        for (ply, (mv, annotation)) in self.moves.iter().zip(review.annotations.iter()).enumerate() {
+            let number = if is_player1(ply) {
+                format!("{}.", (ply + offset) / 2 + 1)
+            } else if ply == 0 {
+                String::from("1...")
+            } else {
+                String::new()
+            };
+            tokens.push(format!("{}{}{}", number, mv.to_notation(), annotation.symbol()));
+        }
+
+        let count = |target: Annotation, player1: bool| review.annotations.iter().enumerate().filter(
+            |(ply, annotation)| **annotation == target && is_player1(*ply) == player1
+        ).count();
+        let summary = format!(
+            "p1: {}?! {}? {}??   p2: {}?! {}? {}??",
+            count(Annotation::Inaccuracy, true), count(Annotation::Mistake, true), count(Annotation::Blunder, true),
+            count(Annotation::Inaccuracy, false), count(Annotation::Mistake, false), count(Annotation::Blunder, false),
+        );
+
+        [
+            TextBox::new(
+                &format!("review (depth {})\n{}\n{}", REVIEW_DEPTH, summary, tokens.join(" ")),
+                30.0,
+                400.0,
+                300.0,
+                200.0,
+                16.0,
+            ).set_color(Color::ui()).render(),
+            draw_graph(review, 30.0, 610.0, 300.0, 150.0),
+        ].concat()
+    }
+}
+
+// player 1's evaluation over the game, with the mistakes and the blunders marked
+fn draw_graph(review: &Review, x: f32, y: f32, w: f32, h: f32) -> Vec<Graphic> {
+    let mut result = vec![
+        Graphic::new_rect(x, y, w, h, 2.0, Color::ui()),
+        Graphic::new_line(x, y + h / 2.0, x + w, y + h / 2.0, 1.0, Color::ui()),
+    ];
+
+    if review.scores.len() < 2 {
+        return result;
+    }
+
+    let step = w / (review.scores.len() - 1) as f32;
+    let point = |ply: usize| (
+        x + ply as f32 * step,
+        y + h / 2.0 - (review.scores[ply] as f32 / GRAPH_RANGE).clamp(-1.0, 1.0) * h / 2.0,
+    );
+
+    for ply in 1..review.scores.len() {
+        let ((x1, y1), (x2, y2)) = (point(ply - 1), point(ply));
+        result.push(Graphic::new_line(x1, y1, x2, y2, 2.0, Color::wall()));
+    }
+
+    // the point after the move shows how much it lost
+    for (ply, annotation) in review.annotations.iter().enumerate() {
+        let color = match annotation {
+            Annotation::Mistake => Color::new(224, 160, 64, 255),
+            Annotation::Blunder => Color::new(224, 64, 64, 255),
+            _ => { continue; },
+        };
+        let (px, py) = point(ply + 1);
+
+        result.push(Graphic::new_circle(px, py, 4.0, 0.0, color));
+    }
+
+    result
+}
+
+const GRAPH_RANGE: f32 = 1000.0;
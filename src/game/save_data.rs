@@ -9,21 +9,10 @@ pub struct GameSaveData {
     pub horizontal_walls: Vec<Vec<bool>>,
     pub cross_walls: Vec<Vec<bool>>,
     pub player1_turn: bool,
+    pub draw_offer: Option<bool>,  // a move declines it, and undoing the move brings it back
 }
 
 impl GameSaveData {
-    pub fn dummy() -> Self {
-        GameSaveData {
-            player1: Player::new(true),
-            player2: Player::new(false),
-            clock: 0.0,
-            vertical_walls: vec![vec![false; 9]; 10],
-            horizontal_walls: vec![vec![false; 9]; 10],
-            cross_walls: vec![vec![false; 10]; 10],
-            player1_turn: true,
-        }
-    }
-
     pub fn from_game(game: &Game) -> Self {
         GameSaveData {
             player1: game.player1.clone(),
@@ -33,6 +22,7 @@ impl GameSaveData {
             horizontal_walls: game.horizontal_walls.clone(),
             cross_walls: game.cross_walls.clone(),
             player1_turn: game.player1_turn,
+            draw_offer: game.draw_offer,
         }
     }
 }
@@ -0,0 +1,63 @@
+use super::{Game, Seat, Spectator};
+use crate::ai::personality::Personality;
+use crate::record::GameRecord;
+
+// An unfinished game, saved as a game record when the player leaves it for the main menu,
+// so that "Load" picks it up again, even after the window is closed.
+impl Game {
+    pub(crate) fn is_in_progress(&self) -> bool {
+        self.outcome.is_none() && !self.moves.is_empty()
+    }
+
+    pub(crate) fn save_game(&self) -> Result<(), String> {
+        self.to_record().save(SAVE_PATH)
+    }
+
+    // the players, the handicap, the limits and the clock come back from the record
+    pub(crate) fn load_game(&mut self) -> Result<(), String> {
+        let record = GameRecord::load(SAVE_PATH)?;
+        record.replay()?;
+
+        if record.reason.is_some() {
+            return Err(String::from("The saved game is over"));
+        }
+
+        let seats = (
+            self.saved_seat(&record.players.0, record.personalities.0),
+            self.saved_seat(&record.players.1, record.personalities.1),
+        );
+        let is_spectating = !seats.0.is_human() && !seats.1.is_human();
+
+        self.handicap = record.handicap.clone();
+        self.move_limit = record.move_limit;
+        self.time_control = record.time_control;
+        self.restart(seats);
+
+        if is_spectating {
+            self.spectator = Some(Spectator::new());
+        }
+
+        for (ply, mv) in record.moves.iter().enumerate() {
+            self.clock += record.times.get(ply).copied().unwrap_or(0.0);
+            self.play_move(*mv);
+        }
+
+        Ok(())
+    }
+
+    // the built-in cpu with the same style, or the human with the same profile
+    fn saved_seat(&mut self, name: &str, personality: Option<Personality>) -> Seat {
+        match personality {
+            Some(personality) => {
+                let last = std::mem::replace(&mut self.personality, personality);
+                let seat = self.cpu_seat();
+                self.personality = last;
+
+                seat
+            },
+            None => self.human_seat(self.profiles.iter().position(|profile| profile.name == name)),
+        }
+    }
+}
+
+pub(super) const SAVE_PATH: &str = "saved_game.txt";
@@ -0,0 +1,202 @@
+use super::Game;
+use crate::agent::Agent;
+use crate::ai::{personality::Personality, strength::Strength};
+use crate::engine::global::GLOBAL_ENV;
+use crate::position::{Move, Position};
+use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+// Who plays one side of the board: a human clicking on it, or an `Agent`.
+// An agent thinks in a background thread, so that the window keeps responding,
+// and `Game::frame` polls it once a frame. While the opponent thinks, the agent ponders in the same way.
+pub struct Seat {
+    pub name: String,
+    pub personality: Option<Personality>,  // of the built-in cpu
+    pub strength: Option<Strength>,  // of the adaptive cpu
+    pub profile: Option<usize>,  // index in `Game::profiles`, of a human
+    state: SeatState,
+    unobserved: Vec<Position>,  // the positions that were played while the agent was busy
+    pondered: Option<u64>,  // hash of the last position it pondered on, so that it does not ponder twice
+}
+
+enum SeatState {
+    Human,
+    Idle(Box<dyn Agent>),
+    Busy(AgentThread),
+}
+
+struct AgentThread {
+    handle: JoinHandle<(Box<dyn Agent>, Option<Move>)>,
+    stop: Arc<AtomicBool>,
+    hash: u64,  // of the position it's working on
+    pondering: bool,
+}
+
+impl Seat {
+    pub fn human() -> Self {
+        Seat {
+            name: String::from("human"),
+            personality: None,
+            strength: None,
+            profile: None,
+            state: SeatState::Human,
+            unobserved: vec![],
+            pondered: None,
+        }
+    }
+
+    pub fn agent(agent: Box<dyn Agent>) -> Self {
+        Seat {
+            name: agent.name(),
+            personality: None,
+            strength: None,
+            profile: None,
+            state: SeatState::Idle(agent),
+            unobserved: vec![],
+            pondered: None,
+        }
+    }
+
+    pub fn is_human(&self) -> bool {
+        matches!(self.state, SeatState::Human)
+    }
+
+    // The thread keeps the agent, so it's not dropped here: it's taken back when the thread ends.
+    fn cancel(&mut self) {
+        if let SeatState::Busy(thread) = &self.state {
+            thread.stop.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn observe(&mut self, position: &Position) {
+        match &mut self.state {
+            SeatState::Idle(agent) => { agent.observe(position); },
+            SeatState::Busy(_) => { self.unobserved.push(position.clone()); },
+            SeatState::Human => {},
+        }
+    }
+
+    // the move of the agent, once it has one for `position`
+    // with its principal variation
+    fn update(&mut self, position: &Position, is_my_turn: bool) -> Option<(Move, Vec<Move>)> {
+        let mut chosen = None;
+
+        if let SeatState::Busy(thread) = &self.state {
+            // the position has changed, or it's no longer the opponent's time
+            if thread.hash != position.hash() || thread.pondering && is_my_turn {
+                thread.stop.store(true, Ordering::Relaxed);
+            }
+        }
+
+        self.state = match std::mem::replace(&mut self.state, SeatState::Human) {
+            SeatState::Busy(thread) if thread.handle.is_finished() => {
+                let cancelled = thread.stop.load(Ordering::Relaxed);
+
+                match thread.handle.join() {
+                    Ok((mut agent, mv)) => {
+                        // the answer of a cancelled search is for a position that is gone
+                        if !cancelled && !thread.pondering {
+                            chosen = mv.map(|mv| (mv, agent.principal_variation()));
+                        }
+
+                        for position in self.unobserved.drain(..) {
+                            agent.observe(&position);
+                        }
+
+                        SeatState::Idle(agent)
+                    },
+
+                    // the agent panicked: let the human play this side
+                    Err(_) => {
+                        self.name = String::from("human");
+                        SeatState::Human
+                    },
+                }
+            },
+            state => state,
+        };
+
+        if chosen.is_some() {
+            return chosen;
+        }
+
+        let may_ponder = unsafe { GLOBAL_ENV.pondering } && self.pondered != Some(position.hash());
+
+        if let SeatState::Idle(_) = self.state {
+            if is_my_turn {
+                self.spawn(position, false);
+            }
+
+            else if may_ponder && position.winner().is_none() {
+                self.pondered = Some(position.hash());
+                self.spawn(position, true);
+            }
+        }
+
+        None
+    }
+
+    fn spawn(&mut self, position: &Position, pondering: bool) {
+        let mut agent = match std::mem::replace(&mut self.state, SeatState::Human) {
+            SeatState::Idle(agent) => agent,
+            state => {
+                self.state = state;
+                return;
+            },
+        };
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_ = stop.clone();
+        let hash = position.hash();
+        let position = position.clone();
+        let deadline = Instant::now() + AGENT_TIME_LIMIT;
+
+        let handle = thread::spawn(move || {
+            if pondering {
+                agent.ponder(&position, &stop_);
+                (agent, None)
+            }
+
+            else {
+                let mv = agent.choose_move(&position, deadline, &stop_);
+                (agent, mv)
+            }
+        });
+
+        self.state = SeatState::Busy(AgentThread { handle, stop, hash, pondering });
+    }
+}
+
+impl Game {
+    // It plays the move of the agent whose turn it is, once it has one.
+    // The other agent ponders meanwhile.
+    pub(super) fn update_seats(&mut self) {
+        let position = Position::from_game(self);
+        let player1_turn = self.player1_turn;
+
+        let chosen1 = self.seats.0.update(&position, player1_turn);
+        let chosen2 = self.seats.1.update(&position, !player1_turn);
+
+        if let Some((mv, pv)) = chosen1.or(chosen2) {
+            let name = if player1_turn { self.seats.0.name.clone() } else { self.seats.1.name.clone() };
+
+            self.play_move(mv);
+            self.explain_move(&position, mv, pv, name);
+        }
+    }
+
+    pub(super) fn cancel_agents(&mut self) {
+        self.seats.0.cancel();
+        self.seats.1.cancel();
+    }
+
+    pub(super) fn notify_agents(&mut self) {
+        let position = Position::from_game(self);
+
+        self.seats.0.observe(&position);
+        self.seats.1.observe(&position);
+    }
+}
+
+const AGENT_TIME_LIMIT: Duration = Duration::from_millis(1500);
@@ -0,0 +1,162 @@
+use super::{Game, Seat};
+use crate::engine::color::Color;
+use crate::engine::graphic::Graphic;
+use crate::engine::widget::textbox::TextBox;
+use crate::popup::Popup;
+use macroquad::rand::gen_range;
+
+// A best-of-N match between two players. Player 1 normally moves first, so the players swap
+// their seats after every game, and the first game's seats can be picked by a coin toss.
+// A handicap follows its player to the other seat.
+pub struct Series {
+    pub length: usize,  // one of `SERIES_LENGTHS`
+    names: (String, String),  // of the players, in the seats they were picked in
+    results: Vec<bool>,  // whether `names.0` won, for every finished game
+    swapped: bool,  // whether `names.0` plays player 2 now
+}
+
+impl Series {
+    fn score(&self) -> (usize, usize) {
+        let wins = self.results.iter().filter(|won| **won).count();
+
+        (wins, self.results.len() - wins)
+    }
+
+    // `Some(true)` when `names.0` has won the match
+    fn winner(&self) -> Option<bool> {
+        let (wins, losses) = self.score();
+        let needed = self.length / 2 + 1;
+
+        if wins >= needed {
+            Some(true)
+        }
+
+        else if losses >= needed {
+            Some(false)
+        }
+
+        else {
+            None
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some()
+    }
+}
+
+impl Game {
+    // right after `restart`, with the seats of the first game
+    // a single game is not a series, but the coin can still be tossed for it
+    pub(super) fn start_series(&mut self, length: usize, coin_toss: bool) {
+        let mut series = Series {
+            length,
+            names: (series_name(&self.seats.0, 1), series_name(&self.seats.1, 2)),
+            results: vec![],
+            swapped: false,
+        };
+
+        if coin_toss {
+            if gen_range(0, 2) == 1 {
+                let seats = std::mem::replace(&mut self.seats, (Seat::human(), Seat::human()));
+                self.handicap = self.handicap.mirrored();
+                self.restart((seats.1, seats.0));
+                series.swapped = true;
+            }
+
+            let first = if series.swapped { &series.names.1 } else { &series.names.0 };
+            self.curr_popup = Popup::new(&format!("Coin toss: {} moves first", first));
+        }
+
+        if length > 1 {
+            self.series = Some(series);
+        }
+    }
+
+    // the players swap their seats for the next game, and a finished match starts over
+    pub(super) fn next_series_game(&mut self, seats: (Seat, Seat)) {
+        let mut series = match self.series.take() {
+            Some(series) => series,
+            None => {
+                self.restart(seats);
+                return;
+            },
+        };
+
+        if series.is_over() {
+            series.results.clear();
+        }
+
+        series.swapped = !series.swapped;
+        self.handicap = self.handicap.mirrored();
+        self.restart((seats.1, seats.0));
+        self.series = Some(series);
+    }
+
+    // a drawn game does not count, so that the match is always won by someone
+    pub(super) fn count_series_game(&mut self) {
+        let player1_won = match self.winner() {
+            Some(player1_won) => player1_won,
+            None => { return; },
+        };
+
+        if let Some(series) = &mut self.series {
+            series.results.push(player1_won != series.swapped);
+        }
+    }
+
+    // when the last move of a finished game is taken back
+    pub(super) fn uncount_series_game(&mut self) {
+        if self.winner().is_none() {
+            return;
+        }
+
+        if let Some(series) = &mut self.series {
+            series.results.pop();
+        }
+    }
+
+    pub(super) fn series_title(&self) -> Option<String> {
+        let series = self.series.as_ref()?;
+        let (wins, losses) = series.score();
+
+        match series.winner()? {
+            true => Some(format!("{} wins the match {}-{}!", series.names.0, wins, losses)),
+            false => Some(format!("{} wins the match {}-{}!", series.names.1, losses, wins)),
+        }
+    }
+
+    pub(super) fn draw_series(&self) -> Vec<Graphic> {
+        let series = match &self.series {
+            Some(series) => series,
+            None => { return vec![]; },
+        };
+
+        let (wins, losses) = series.score();
+
+        TextBox::new(
+            &format!(
+                "best of {}{}\n{} {} : {} {}",
+                series.length, if series.is_over() { ", over" } else { "" }, series.names.0, wins, losses, series.names.1,
+            ),
+            30.0,
+            270.0,
+            210.0,
+            60.0,
+            21.0,
+        ).set_color(Color::ui()).align_center().render()
+    }
+}
+
+// the guests have no names, so they're told apart by their first seat
+fn series_name(seat: &Seat, player: usize) -> String {
+    if seat.is_human() && seat.profile.is_none() {
+        format!("guest {}", player)
+    }
+
+    else {
+        seat.name.clone()
+    }
+}
+
+pub const SERIES_LENGTHS: [usize; 4] = [1, 3, 5, 7];
@@ -0,0 +1,352 @@
+use super::{Game, Seat, BOARD_SIZE, level_label, personality_label, time_control_label};
+use super::series::SERIES_LENGTHS;
+use super::ending::{MOVE_LIMITS, TIME_CONTROLS};
+use crate::engine::color::Color;
+use crate::engine::graphic::Graphic;
+use crate::engine::inputs::Inputs;
+use crate::engine::widget::{button::Button, textbox::TextBox};
+use crate::handicap::{Handicap, MAX_HEAD_START};
+use crate::menu::{Mode, NewGame};
+use crate::popup::Popup;
+
+// The new-game dialog over the board. The left column picks the players and the kind of game: the profiles
+// of the humans, the style and the level of the cpu, the length of the match, the move limit and the time control.
+// The right column is the handicap. The game behind it goes on until "Start" is clicked.
+pub struct Setup {
+    vs_cpu: bool,
+    profiles: (Option<usize>, Option<usize>),  // indices in `Game::profiles`, `None` for a guest
+    series_length: usize,  // one of `SERIES_LENGTHS`, 1 for a single game
+    coin_toss: bool,
+    move_limit: Option<usize>,
+    time_control: Option<usize>,
+    handicap: Handicap,
+    buttons: Vec<(Button, SetupAction, (f32, f32))>,  // with the position in the panel
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum SetupAction {
+    Profile(bool),  // is player 1
+    Personality,
+    Level,
+    SeriesLength,
+    CoinToss,
+    MoveLimit,
+    TimeControl,
+    Walls(bool),
+    HeadStart(bool),
+    WallFor,
+    FirstMove,
+    Start,
+    Cancel,
+}
+
+impl Game {
+    // it starts with the settings of the last game
+    pub(super) fn open_setup(&mut self, vs_cpu: bool) {
+        let mut setup = Setup {
+            vs_cpu,
+            profiles: (self.seats.0.profile, if vs_cpu { None } else { self.seats.1.profile }),
+            series_length: self.series.as_ref().map(|series| series.length).unwrap_or(1),
+            coin_toss: false,
+            move_limit: self.move_limit,
+            time_control: self.time_control,
+            handicap: self.handicap.clone(),
+            buttons: vec![],
+        };
+
+        self.label_setup(&mut setup);
+        self.setup = Some(setup);
+    }
+
+    // the labels show the current choices, so the buttons are built again after every click
+    fn label_setup(&self, setup: &mut Setup) {
+        let profile_label = |player1: bool, profile: Option<usize>| format!(
+            "P{}: {}",
+            player_number(player1),
+            profile.map(|index| self.profiles[index].name.as_str()).unwrap_or("guest"),
+        );
+        let handicap = &setup.handicap;
+
+        let mut left = vec![(profile_label(true, setup.profiles.0), SetupAction::Profile(true))];
+
+        if setup.vs_cpu {
+            left.push((personality_label(self.personality), SetupAction::Personality));
+            left.push((level_label(self.adaptive), SetupAction::Level));
+        }
+
+        else {
+            left.push((profile_label(false, setup.profiles.1), SetupAction::Profile(false)));
+        }
+
+        left.push((series_label(setup.series_length), SetupAction::SeriesLength));
+        left.push((format!("Coin toss: {}", if setup.coin_toss { "on" } else { "off" }), SetupAction::CoinToss));
+        left.push((move_limit_label(setup.move_limit), SetupAction::MoveLimit));
+        left.push((time_control_label(setup.time_control), SetupAction::TimeControl));
+
+        let right = vec![
+            (format!("P1 walls: {}", handicap.walls.0), SetupAction::Walls(true)),
+            (format!("P2 walls: {}", handicap.walls.1), SetupAction::Walls(false)),
+            (format!("P1 head start: {}", handicap.head_start.0), SetupAction::HeadStart(true)),
+            (format!("P2 head start: {}", handicap.head_start.1), SetupAction::HeadStart(false)),
+            (match handicap.wall_for {
+                Some(player1) => format!("Wall for: P{}", player_number(player1)),
+                None => String::from("Wall for: nobody"),
+            }, SetupAction::WallFor),
+            (format!("First move: P{}", player_number(handicap.player1_first)), SetupAction::FirstMove),
+        ];
+
+        let bottom = vec![(String::from("Start"), SetupAction::Start), (String::from("Cancel"), SetupAction::Cancel)];
+
+        setup.buttons = vec![];
+
+        for (column, rows) in [left, right, bottom].iter().enumerate() {
+            for (row, (label, action)) in rows.iter().enumerate() {
+                let offset = if column < 2 {
+                    (COLUMN_X + column as f32 * COLUMN_GAP, ROW_Y + row as f32 * ROW_GAP)
+                } else {
+                    (COLUMN_X + row as f32 * COLUMN_GAP, PANEL_H - 110.0)
+                };
+
+                setup.buttons.push((Button::new(0.0, 0.0, label), *action, offset));
+            }
+        }
+    }
+
+    pub(super) fn update_setup(&mut self, inputs: &Inputs, mouse_pos: (f32, f32), box_x: f32, box_y: f32) {
+        let mut setup = match self.setup.take() {
+            Some(setup) => setup,
+            None => { return; },
+        };
+
+        let (panel_x, panel_y, _, _) = panel_rect(box_x, box_y);
+        let mut clicked = None;
+
+        for (button, action, (x, y)) in setup.buttons.iter_mut() {
+            button.move_to(panel_x + *x, panel_y + *y);
+
+            if button.check_mouse(mouse_pos) && inputs.mouse_pressed[0] {
+                clicked = Some(*action);
+            }
+        }
+
+        let handicap = &mut setup.handicap;
+
+        match clicked {
+            Some(SetupAction::Profile(player1)) => {
+                if player1 {
+                    setup.profiles.0 = self.next_profile(setup.profiles.0, setup.profiles.1);
+                }
+
+                else {
+                    setup.profiles.1 = self.next_profile(setup.profiles.1, setup.profiles.0);
+                }
+            },
+            Some(SetupAction::Personality) => {
+                self.personality = self.personality.next();
+            },
+            Some(SetupAction::Level) => {
+                self.adaptive = !self.adaptive;
+            },
+            Some(SetupAction::SeriesLength) => {
+                let index = SERIES_LENGTHS.iter().position(|length| *length == setup.series_length).unwrap_or(0);
+                setup.series_length = SERIES_LENGTHS[(index + 1) % SERIES_LENGTHS.len()];
+            },
+            Some(SetupAction::CoinToss) => {
+                setup.coin_toss = !setup.coin_toss;
+            },
+            Some(SetupAction::MoveLimit) => {
+                let index = MOVE_LIMITS.iter().position(|limit| *limit == setup.move_limit).unwrap_or(0);
+                setup.move_limit = MOVE_LIMITS[(index + 1) % MOVE_LIMITS.len()];
+            },
+            Some(SetupAction::TimeControl) => {
+                let index = TIME_CONTROLS.iter().position(|seconds| *seconds == setup.time_control).unwrap_or(0);
+                setup.time_control = TIME_CONTROLS[(index + 1) % TIME_CONTROLS.len()];
+            },
+            Some(SetupAction::Walls(player1)) => {
+                let walls = if player1 { &mut handicap.walls.0 } else { &mut handicap.walls.1 };
+                *walls = if *walls <= MIN_WALLS { 10 } else { *walls - WALLS_STEP };
+            },
+            Some(SetupAction::HeadStart(player1)) => {
+                let head_start = if player1 { &mut handicap.head_start.0 } else { &mut handicap.head_start.1 };
+                *head_start = (*head_start + 1) % (MAX_HEAD_START + 1);
+            },
+            Some(SetupAction::WallFor) => {
+                handicap.wall_for = match handicap.wall_for {
+                    None => Some(true),
+                    Some(true) => Some(false),
+                    Some(false) => None,
+                };
+            },
+            Some(SetupAction::FirstMove) => {
+                handicap.player1_first = !handicap.player1_first;
+            },
+            Some(SetupAction::Start) => {
+                self.move_limit = setup.move_limit;
+                self.time_control = setup.time_control;
+                self.handicap = setup.handicap;
+
+                let player1 = self.human_seat(setup.profiles.0);
+                let player2 = if setup.vs_cpu { self.cpu_seat() } else { self.human_seat(setup.profiles.1) };
+
+                self.curr_popup = Popup::new("Restart");
+                self.restart((player1, player2));
+                self.start_series(setup.series_length, setup.coin_toss);
+                return;
+            },
+            Some(SetupAction::Cancel) => { return; },
+            None => {},
+        }
+
+        if clicked.is_some() {
+            self.label_setup(&mut setup);
+        }
+
+        self.setup = Some(setup);
+    }
+
+    // the choices of the main menu's "New game", from the last game
+    pub(crate) fn new_game_options(&self) -> NewGame {
+        let mode = match (self.seats.0.is_human(), self.seats.1.is_human()) {
+            (true, true) => Mode::VsHuman,
+            (false, false) => Mode::Watch,
+            _ => Mode::VsCpu,
+        };
+
+        NewGame {
+            mode,
+            profile: self.last_profile(),
+            personality: self.personality,
+            adaptive: self.adaptive,
+            time_control: self.time_control,
+        }
+    }
+
+    // a game from the main menu is a single game without a handicap
+    pub(crate) fn start_new_game(&mut self, options: &NewGame) {
+        self.personality = options.personality;
+        self.adaptive = options.adaptive;
+        self.time_control = options.time_control;
+        self.handicap = Handicap::default();
+
+        match options.mode {
+            Mode::VsCpu => {
+                let seats = (self.human_seat(options.profile), self.cpu_seat());
+                self.restart(seats);
+            },
+            Mode::VsHuman => {
+                let seats = (self.human_seat(options.profile), Seat::human());
+                self.restart(seats);
+            },
+            Mode::Watch => { self.spectate(); },
+        }
+    }
+
+    // guest -> the profiles in order -> guest, skipping the one that the other player took
+    fn next_profile(&self, curr: Option<usize>, other: Option<usize>) -> Option<usize> {
+        let mut next = curr;
+
+        loop {
+            next = match next {
+                None if !self.profiles.is_empty() => Some(0),
+                Some(index) if index + 1 < self.profiles.len() => Some(index + 1),
+                _ => None,
+            };
+
+            if next.is_none() || next != other {
+                return next;
+            }
+        }
+    }
+
+    pub(super) fn human_seat(&self, profile: Option<usize>) -> Seat {
+        let mut seat = Seat::human();
+
+        if let Some(index) = profile {
+            seat.name = self.profiles[index].name.clone();
+            seat.profile = Some(index);
+        }
+
+        seat
+    }
+
+    pub(super) fn draw_setup(&mut self, box_x: f32, box_y: f32) -> Vec<Graphic> {
+        let mut setup = match self.setup.take() {
+            Some(setup) => setup,
+            None => { return vec![]; },
+        };
+
+        let (panel_x, panel_y, panel_w, panel_h) = panel_rect(box_x, box_y);
+        let title = if setup.vs_cpu { "New game: vs CPU" } else { "New game: vs HUMAN" };
+        let mut result = vec![
+            vec![Graphic::new_round_rect(panel_x, panel_y, panel_w, panel_h, 16.0, 0.0, Color::new(16, 16, 16, 224))],
+            TextBox::new(title, panel_x, panel_y + 20.0, panel_w, 50.0, 32.0).set_color(Color::ui()).align_center().render(),
+            TextBox::new("Handicap", panel_x + COLUMN_X + COLUMN_GAP, panel_y + ROW_Y - 30.0, 180.0, 24.0, 18.0).set_color(Color::ui()).align_center().render(),
+        ];
+
+        for (button, action, (x, y)) in setup.buttons.iter_mut() {
+            result.push(button.render());
+
+            // the avatar on the left of the name
+            let profile = match action {
+                SetupAction::Profile(true) => setup.profiles.0,
+                SetupAction::Profile(false) => setup.profiles.1,
+                _ => None,
+            };
+
+            if let Some(profile) = profile {
+                result.push(vec![Graphic::new_circle(
+                    panel_x + *x - 12.0,
+                    panel_y + *y + 16.0,
+                    8.0, 0.0,
+                    self.profiles[profile].avatar_color(),
+                )]);
+            }
+        }
+
+        if self.profiles.is_empty() {
+            result.push(TextBox::new(
+                "No profiles yet: `quoridor profile add NAME`",
+                panel_x, panel_y + panel_h - 50.0, panel_w, 30.0, 18.0,
+            ).set_color(Color::ui()).align_center().render());
+        }
+
+        self.setup = Some(setup);
+        result.concat()
+    }
+}
+
+fn player_number(player1: bool) -> usize {
+    if player1 { 1 } else { 2 }
+}
+
+fn series_label(length: usize) -> String {
+    if length == 1 {
+        String::from("Match: single game")
+    }
+
+    else {
+        format!("Match: best of {}", length)
+    }
+}
+
+fn move_limit_label(move_limit: Option<usize>) -> String {
+    match move_limit {
+        Some(limit) => format!("Move limit: {}", limit),
+        None => String::from("Move limit: none"),
+    }
+}
+
+fn panel_rect(box_x: f32, box_y: f32) -> (f32, f32, f32, f32) {
+    (box_x + (BOARD_SIZE - PANEL_W) / 2.0, box_y + (BOARD_SIZE - PANEL_H) / 2.0, PANEL_W, PANEL_H)
+}
+
+const PANEL_W: f32 = 460.0;
+const PANEL_H: f32 = 570.0;
+const COLUMN_X: f32 = 40.0;
+const COLUMN_GAP: f32 = 200.0;
+const ROW_Y: f32 = 110.0;
+const ROW_GAP: f32 = 50.0;
+
+// the walls of a player cycle 10 -> 8 -> ... -> `MIN_WALLS` -> 10
+const WALLS_STEP: usize = 2;
+const MIN_WALLS: usize = 2;
@@ -0,0 +1,237 @@
+use super::{Game, GameState};
+use crate::engine::color::Color;
+use crate::engine::graphic::Graphic;
+use crate::engine::inputs::Inputs;
+use crate::engine::keys::{KEY_MINUS, KEY_EQUAL, KEY_R, KEY_RIGHT, KEY_SPACE};
+use crate::engine::widget::{button::Button, textbox::TextBox};
+use std::time::{Duration, Instant};
+
+// CPU vs CPU: the seats are both agents, and the human only watches.
+// It can be paused, stepped one move at a time, slowed down, and rematched forever.
+pub struct Spectator {
+    paused: bool,
+    steps: usize,  // moves to play while paused
+    delay: usize,  // index of `MOVE_DELAYS`
+    auto_rematch: bool,
+    score: (usize, usize),  // (player1, player2)
+    last_move_at: Instant,
+    finished_at: Option<Instant>,
+    buttons: Vec<Button>,
+}
+
+impl Spectator {
+    pub(super) fn new() -> Self {
+        let mut spectator = Spectator {
+            paused: false,
+            steps: 0,
+            delay: 1,
+            auto_rematch: false,
+            score: (0, 0),
+            last_move_at: Instant::now(),
+            finished_at: None,
+            buttons: vec![],
+        };
+
+        spectator.update_buttons();
+        spectator
+    }
+
+    // the labels show the current settings, so the buttons are rebuilt whenever they change
+    fn update_buttons(&mut self) {
+        let labels = [
+            String::from(if self.paused { "Resume" } else { "Pause" }),
+            String::from("Step"),
+            format!("Delay: {:.1}s", MOVE_DELAYS[self.delay].as_secs_f32()),
+            format!("Auto rematch: {}", if self.auto_rematch { "on" } else { "off" }),
+        ];
+
+        self.buttons = labels.iter().enumerate().map(
+            |(index, label)| Button::new(30.0, 30.0 + index as f32 * 60.0, label)
+        ).collect();
+    }
+
+    pub(super) fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.steps = 0;
+        self.update_buttons();
+    }
+
+    fn step(&mut self) {
+        if !self.paused {
+            self.paused = true;
+            self.update_buttons();
+        }
+
+        self.steps += 1;
+    }
+
+    fn cycle_delay(&mut self) {
+        self.delay = (self.delay + 1) % MOVE_DELAYS.len();
+        self.update_buttons();
+    }
+
+    fn change_delay(&mut self, faster: bool) {
+        self.delay = if faster { self.delay.saturating_sub(1) } else { (self.delay + 1).min(MOVE_DELAYS.len() - 1) };
+        self.update_buttons();
+    }
+
+    fn toggle_auto_rematch(&mut self) {
+        self.auto_rematch = !self.auto_rematch;
+        self.update_buttons();
+    }
+}
+
+impl Game {
+    pub(super) fn spectate(&mut self) {
+        self.restart((self.cpu_seat(), self.cpu_seat()));
+        self.spectator = Some(Spectator::new());
+    }
+
+    pub(super) fn is_paused(&self) -> bool {
+        self.spectator.as_ref().map(|spectator| spectator.paused).unwrap_or(false)
+    }
+
+    // `update_seats`, unless the spectator holds the agents back
+    pub(super) fn update_agents(&mut self) {
+        let may_move = match &self.spectator {
+            Some(spectator) => (!spectator.paused || spectator.steps > 0)
+                && spectator.last_move_at.elapsed() >= MOVE_DELAYS[spectator.delay],
+            None => true,
+        };
+
+        if !may_move {
+            return;
+        }
+
+        let moves = self.history.len();
+        self.update_seats();
+
+        if let Some(spectator) = &mut self.spectator {
+            if self.history.len() > moves {
+                spectator.last_move_at = Instant::now();
+                spectator.steps = spectator.steps.saturating_sub(1);
+            }
+        }
+    }
+
+    pub(super) fn update_spectator(&mut self, inputs: &Inputs, mouse_pos: (f32, f32)) {
+        let winner = self.winner();
+        let spectator = match &mut self.spectator {
+            Some(spectator) => spectator,
+            None => { return; },
+        };
+
+        for button in spectator.buttons.iter_mut() {
+            button.check_mouse(mouse_pos);
+        }
+
+        if inputs.mouse_pressed[0] {
+            if spectator.buttons[0].check_mouse(mouse_pos) {
+                spectator.toggle_pause();
+            }
+
+            else if spectator.buttons[1].check_mouse(mouse_pos) {
+                spectator.step();
+            }
+
+            else if spectator.buttons[2].check_mouse(mouse_pos) {
+                spectator.cycle_delay();
+            }
+
+            else if spectator.buttons[3].check_mouse(mouse_pos) {
+                spectator.toggle_auto_rematch();
+            }
+        }
+
+        if inputs.key_pressed[KEY_SPACE] {
+            spectator.toggle_pause();
+        }
+
+        if inputs.key_pressed[KEY_RIGHT] {
+            spectator.step();
+        }
+
+        if inputs.key_pressed[KEY_MINUS] {
+            spectator.change_delay(true);
+        }
+
+        if inputs.key_pressed[KEY_EQUAL] {
+            spectator.change_delay(false);
+        }
+
+        if inputs.key_pressed[KEY_R] {
+            spectator.toggle_auto_rematch();
+        }
+
+        if self.state != GameState::GameOver {
+            spectator.finished_at = None;
+            return;
+        }
+
+        // the game has just ended
+        if spectator.finished_at.is_none() {
+            spectator.finished_at = Some(Instant::now());
+
+            match winner {
+                Some(true) => { spectator.score.0 += 1; },
+                Some(false) => { spectator.score.1 += 1; },
+                None => {},
+            }
+        }
+
+        let rematch = spectator.auto_rematch
+            && !spectator.paused
+            && spectator.finished_at.map(|at| at.elapsed() >= REMATCH_DELAY).unwrap_or(false);
+
+        if rematch {
+            self.rematch();
+
+            if let Some(spectator) = &mut self.spectator {
+                spectator.finished_at = None;
+                spectator.last_move_at = Instant::now();
+            }
+        }
+    }
+
+    pub(super) fn draw_spectator(&mut self) -> Vec<Graphic> {
+        let spectator = match &mut self.spectator {
+            Some(spectator) => spectator,
+            None => { return vec![]; },
+        };
+
+        let mut result = vec![];
+
+        for button in spectator.buttons.iter_mut() {
+            result.push(button.render());
+        }
+
+        result.push(TextBox::new(
+            &format!("score  {} : {}", spectator.score.0, spectator.score.1),
+            30.0,
+            270.0,
+            210.0,
+            30.0,
+            21.0,
+        ).set_color(Color::ui()).align_center().render());
+
+        result.push(TextBox::new(
+            "space: pause, right: step\n-/=: delay, r: rematch",
+            30.0,
+            300.0,
+            210.0,
+            60.0,
+            16.0,
+        ).set_color(Color::ui()).align_center().render());
+
+        result.concat()
+    }
+}
+
+const MOVE_DELAYS: [Duration; 5] = [
+    Duration::ZERO,
+    Duration::from_millis(500),
+    Duration::from_millis(1000),
+    Duration::from_millis(2000),
+    Duration::from_millis(4000),
+];
+const REMATCH_DELAY: Duration = Duration::from_millis(3000);
@@ -0,0 +1,194 @@
+use super::{Game, BOARD_SIZE};
+use crate::engine::color::Color;
+use crate::engine::file_io::create_dir;
+use crate::engine::graphic::Graphic;
+use crate::engine::inputs::Inputs;
+use crate::engine::keys::KEY_TAB;
+use crate::engine::widget::{button::Button, textbox::TextBox};
+use crate::popup::Popup;
+use crate::position::Position;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The panel over the board when a game ends. "Review" hides it, so that the board and the review are visible,
+// and tab brings it back.
+pub struct Summary {
+    total_moves: usize,
+    walls: (usize, usize),  // placed by (player1, player2)
+    times: (f32, f32),  // seconds used by (player1, player2)
+    longest_think: Option<(bool, f32)>,  // (is player 1, seconds)
+    paths: Vec<(u8, u8)>,  // path lengths of (player1, player2), before each move and after the last
+    buttons: Vec<Button>,
+    visible: bool,
+}
+
+impl Game {
+    pub(super) fn open_summary(&mut self) {
+        let mut times = (0.0, 0.0);
+        let mut longest_think: Option<(bool, f32)> = None;
+
+        for (before, time) in self.history.iter().zip(self.move_times()) {
+            let player1 = before.player1_turn;
+
+            if player1 { times.0 += time; } else { times.1 += time; }
+
+            if longest_think.map(|(_, longest)| time > longest).unwrap_or(true) {
+                longest_think = Some((player1, time));
+            }
+        }
+
+        let mut position = self.handicap.position();
+        let lengths = |position: &Position| (position.distance(true).unwrap_or(0), position.distance(false).unwrap_or(0));
+        let mut paths = vec![lengths(&position)];
+
+        for mv in self.moves.iter() {
+            position.apply(*mv);
+            paths.push(lengths(&position));
+        }
+
+        let rematch = if self.series.as_ref().map(|series| !series.is_over()).unwrap_or(false) { "Next game" } else { "Rematch" };
+
+        self.summary = Some(Summary {
+            total_moves: self.moves.len(),
+            walls: self.walls_placed(),
+            times, longest_think, paths,
+            buttons: [rematch, "Save", "Review"].iter().map(|label| Button::new(0.0, 0.0, label)).collect(),
+            visible: true,
+        });
+    }
+
+    pub(super) fn update_summary(&mut self, inputs: &Inputs, mouse_pos: (f32, f32), box_x: f32, box_y: f32) {
+        let summary = match &mut self.summary {
+            Some(summary) => summary,
+            None => { return; },
+        };
+
+        if inputs.key_pressed[KEY_TAB] {
+            summary.visible = !summary.visible;
+        }
+
+        if !summary.visible {
+            return;
+        }
+
+        let (panel_x, panel_y, _, panel_h) = panel_rect(box_x, box_y);
+
+        for (index, button) in summary.buttons.iter_mut().enumerate() {
+            button.move_to(panel_x + 15.0 + index as f32 * 195.0, panel_y + panel_h - 60.0);
+            button.check_mouse(mouse_pos);
+        }
+
+        if !inputs.mouse_pressed[0] {
+            return;
+        }
+
+        if summary.buttons[0].check_mouse(mouse_pos) {
+            self.curr_popup = Popup::new("Restart");
+            self.rematch();
+        }
+
+        else if summary.buttons[1].check_mouse(mouse_pos) {
+            self.curr_popup = match self.save_record() {
+                Ok(path) => Popup::new(&format!("Saved to {}", path)),
+                Err(e) => Popup::new(&e),
+            };
+        }
+
+        else if summary.buttons[2].check_mouse(mouse_pos) {
+            summary.visible = false;
+        }
+    }
+
+    // the same kind of players, with the cpu settings of now
+    // in a match, it's the next game
+    pub(super) fn rematch(&mut self) {
+        let player1 = if self.seats.0.is_human() { self.human_seat(self.seats.0.profile) } else { self.cpu_seat() };
+        let player2 = if self.seats.1.is_human() { self.human_seat(self.seats.1.profile) } else { self.cpu_seat() };
+        let spectator = self.spectator.take();
+
+        self.next_series_game((player1, player2));
+        self.spectator = spectator;
+    }
+
+    // in `RECORDS_DIR`, named after the time
+    fn save_record(&self) -> Result<String, String> {
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0);
+        let path = format!("{}/{}.txt", RECORDS_DIR, seconds);
+
+        create_dir(RECORDS_DIR).map_err(|_| format!("Failed to create directory: {}", RECORDS_DIR))?;
+        self.to_record().save(&path)?;
+
+        Ok(path)
+    }
+
+    pub(super) fn draw_summary(&mut self, box_x: f32, box_y: f32, title_color: Color) -> Vec<Graphic> {
+        let title = self.series_title().unwrap_or(self.ending_message());
+        let summary = match &mut self.summary {
+            Some(summary) if summary.visible => summary,
+            _ => { return vec![]; },
+        };
+
+        let (panel_x, panel_y, panel_w, panel_h) = panel_rect(box_x, box_y);
+        let final_paths = summary.paths.last().copied().unwrap_or((0, 0));
+        let longest_think = match summary.longest_think {
+            Some((player1, time)) => format!("{:.1}s by p{}", time, if player1 { 1 } else { 2 }),
+            None => String::from("-"),
+        };
+        let stats = [
+            format!("moves: {}", summary.total_moves),
+            format!("walls placed: p1 {}, p2 {}", summary.walls.0, summary.walls.1),
+            format!("time used: p1 {:.1}s, p2 {:.1}s", summary.times.0, summary.times.1),
+            format!("longest think: {}", longest_think),
+            format!("final path lengths: p1 {}, p2 {}", final_paths.0, final_paths.1),
+        ];
+
+        let mut buttons = vec![];
+
+        for button in summary.buttons.iter_mut() {
+            buttons.push(button.render());
+        }
+
+        [
+            vec![Graphic::new_round_rect(panel_x, panel_y, panel_w, panel_h, 16.0, 0.0, Color::new(16, 16, 16, 224))],
+            TextBox::new(&title, panel_x, panel_y + 10.0, panel_w, 50.0, 36.0).set_color(title_color).align_center().render(),
+            TextBox::new(&stats.join("\n"), panel_x + 30.0, panel_y + 70.0, panel_w - 60.0, 130.0, 21.0).set_color(Color::ui()).render(),
+            draw_path_chart(&summary.paths, panel_x + 30.0, panel_y + 220.0, panel_w - 60.0, 190.0),
+            buttons.concat(),
+        ].concat()
+    }
+}
+
+fn panel_rect(box_x: f32, box_y: f32) -> (f32, f32, f32, f32) {
+    (box_x + (BOARD_SIZE - PANEL_W) / 2.0, box_y + (BOARD_SIZE - PANEL_H) / 2.0, PANEL_W, PANEL_H)
+}
+
+// both path lengths over the game, in the players' colors
+fn draw_path_chart(paths: &[(u8, u8)], x: f32, y: f32, w: f32, h: f32) -> Vec<Graphic> {
+    let mut result = vec![
+        Graphic::new_line(x, y + h, x + w, y + h, 2.0, Color::ui()),
+        Graphic::new_line(x, y, x, y + h, 2.0, Color::ui()),
+    ];
+
+    let longest = paths.iter().map(|(path1, path2)| *path1.max(path2)).max().unwrap_or(0).max(1) as f32;
+
+    if paths.len() < 2 {
+        return result;
+    }
+
+    let step = w / (paths.len() - 1) as f32;
+    let point = |ply: usize, length: u8| (x + ply as f32 * step, y + h - length as f32 / longest * h);
+
+    for ply in 1..paths.len() {
+        let ((x1, y1), (x2, y2)) = (point(ply - 1, paths[ply - 1].0), point(ply, paths[ply].0));
+        result.push(Graphic::new_line(x1, y1, x2, y2, 2.0, Color::player1_normal()));
+
+        let ((x1, y1), (x2, y2)) = (point(ply - 1, paths[ply - 1].1), point(ply, paths[ply].1));
+        result.push(Graphic::new_line(x1, y1, x2, y2, 2.0, Color::player2_normal()));
+    }
+
+    result.extend(TextBox::new(&format!("{}", longest), x - 30.0, y - 10.0, 25.0, 20.0, 16.0).set_color(Color::ui()).render());
+    result
+}
+
+const PANEL_W: f32 = 600.0;
+const PANEL_H: f32 = 480.0;
+const RECORDS_DIR: &str = "records";
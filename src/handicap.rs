@@ -0,0 +1,80 @@
+use crate::position::{Move, Position};
+
+// How a game with a handicap starts. The default is the normal start.
+// Player 1 always goes from the column `a` to `i`, and player 2 the other way.
+#[derive(Clone, PartialEq)]
+pub struct Handicap {
+    pub walls: (usize, usize),  // in hand at the start, of (player1, player2)
+    pub head_start: (i32, i32),  // columns that each pawn starts ahead of its home column
+    pub wall_for: Option<bool>,  // a wall in front of the other pawn, for player 1 if `Some(true)`
+    pub player1_first: bool,
+}
+
+impl Default for Handicap {
+    fn default() -> Self {
+        Handicap {
+            walls: (10, 10),
+            head_start: (0, 0),
+            wall_for: None,
+            player1_first: true,
+        }
+    }
+}
+
+impl Handicap {
+    pub fn position(&self) -> Position {
+        let mut position = Position::new();
+        position.player1 = (self.head_start.0, 4);
+        position.player2 = (8 - self.head_start.1, 4);
+
+        if let Some(mv) = self.placed_wall() {
+            position.apply(mv);
+        }
+
+        position.walls = self.walls;
+        position.player1_turn = self.player1_first;
+        position.update_hash();
+
+        position
+    }
+
+    // a vertical wall right in front of the pawn of the other player
+    pub fn placed_wall(&self) -> Option<Move> {
+        match self.wall_for? {
+            true => Some(Move::Vertical((8 - self.head_start.1) as usize, 3)),
+            false => Some(Move::Vertical((self.head_start.0 + 1) as usize, 3)),
+        }
+    }
+
+    // the same handicap for the same player, after the players swapped their seats
+    // the first move stays with the seat, so that it alternates in a match
+    pub fn mirrored(&self) -> Self {
+        Handicap {
+            walls: (self.walls.1, self.walls.0),
+            head_start: (self.head_start.1, self.head_start.0),
+            wall_for: self.wall_for.map(|player1| !player1),
+            player1_first: self.player1_first,
+        }
+    }
+
+    // roughly how many rating points the handicap is worth to player 1, compared to the normal start
+    pub fn advantage(&self) -> i32 {
+        let walls = (self.walls.0 as i32 - self.walls.1 as i32) * WALL_VALUE;
+        let head_start = (self.head_start.0 - self.head_start.1) * COLUMN_VALUE;
+        let wall_for = match self.wall_for {
+            Some(true) => PLACED_WALL_VALUE,
+            Some(false) => -PLACED_WALL_VALUE,
+            None => 0,
+        };
+        let first = if self.player1_first { 0 } else { -TEMPO_VALUE };
+
+        walls + head_start + wall_for + first
+    }
+}
+
+pub const MAX_HEAD_START: i32 = 2;
+
+const WALL_VALUE: i32 = 30;
+const COLUMN_VALUE: i32 = 60;
+const PLACED_WALL_VALUE: i32 = 50;
+const TEMPO_VALUE: i32 = 40;
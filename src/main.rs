@@ -1,15 +1,29 @@
 mod game;
 mod engine;
 
+mod agent;
+mod board;
 mod ai;
+mod cli;
+mod confirm;
 mod dfs;
+mod handicap;
+mod menu;
 mod player;
+mod position;
+mod profile;
+mod puzzle;
+mod rating;
+mod record;
+mod screen;
+mod settings;
+mod tutorial;
 mod popup;
 mod mouse_trace;
 
 use macroquad::prelude::*;
-use engine::context::Context;
-use engine::global::{EnvMessage, GLOBAL_ENV};
+use engine::stack::ContextStack;
+use engine::global::{global_env, EnvMessage, GLOBAL_ENV};
 use std::{time, thread};
 
 // configure the game window here
@@ -24,8 +38,21 @@ fn conf() -> Conf {
     }
 }
 
-#[macroquad::main(conf)]
-async fn main() {
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if let Ok(now) = time::SystemTime::now().duration_since(time::UNIX_EPOCH) {
+        macroquad::rand::srand(now.as_nanos() as u64);
+    }
+
+    if cli::run(&args) {
+        return;
+    }
+
+    macroquad::Window::from_config(conf(), run());
+}
+
+async fn run() {
 
     let mut sound_manager = engine::sound::SoundManager::new(vec![]);
     let mut fonts = vec![];
@@ -44,7 +71,7 @@ async fn main() {
             sound_manager = engine::sound::SoundManager::new(sounds);
         }
         Err(err) => {
-            unsafe { GLOBAL_ENV.raise_error(&err); }
+            global_env().raise_error(&err);
         }
     }
 
@@ -58,7 +85,7 @@ async fn main() {
         match engine::loader::default_font() {
 
             Err(err) => {
-                unsafe { GLOBAL_ENV.raise_error(&err); }
+                global_env().raise_error(&err);
             }
             Ok(f) => {
                 unsafe { GLOBAL_ENV.is_font_available = true; }
@@ -71,7 +98,8 @@ async fn main() {
 
     unsafe { GLOBAL_ENV.screen_size = (macroquad::window::screen_width(), macroquad::window::screen_height()); }
 
-    let mut curr_game: Box<dyn Context> = Box::new(game::Game::new());
+    let mut focus = engine::inputs::Focus::new();
+    let mut contexts = ContextStack::new(Box::new(menu::MainMenu::new(Box::new(game::Game::new()))));
 
     'game_loop: loop {
         let frame_begin = time::Instant::now();
@@ -92,11 +120,11 @@ async fn main() {
             GLOBAL_ENV.screen_size = new_screen_size;
         }
 
-        let inputs = engine::inputs::Inputs::poll(is_screen_size_changed);
+        let mut inputs = engine::inputs::Inputs::poll(is_screen_size_changed);
+        inputs.is_focused = focus.poll(&inputs);
 
         // run a frame
-        let (g, graphics, sound_actions) = curr_game.frame(inputs);
-        curr_game = g;
+        let (graphics, sound_actions) = contexts.frame(inputs);
 
         // handle GLOBAL_ENV.messages
         unsafe {
@@ -107,9 +135,15 @@ async fn main() {
                         break 'game_loop;
                     },
                     EnvMessage::Error(message) => {
-                        curr_game = Box::new(engine::error::ErrorHandler::new(message));
+                        contexts.reset(Box::new(engine::error::ErrorHandler::new(message)));
                         continue 'game_loop;
                     },
+                    EnvMessage::PushContext(context) => {
+                        contexts.push(context);
+                    },
+                    EnvMessage::PopContext(result) => {
+                        contexts.pop(result);
+                    },
                     EnvMessage::ShowCursor(show) => {
                         show_mouse(show);
                     },
@@ -0,0 +1,220 @@
+mod new_game;
+
+pub use new_game::{Mode, NewGame};
+
+use crate::board::{self, Board};
+use crate::confirm::Confirm;
+use crate::engine::color::Color;
+use crate::engine::context::Context;
+use crate::engine::global::global_env;
+use crate::engine::graphic::Graphic;
+use crate::engine::inputs::Inputs;
+use crate::engine::keys::KEY_ESCAPE;
+use crate::engine::sound::SoundAction;
+use crate::engine::transition;
+use crate::engine::widget::{button::Button, textbox::TextBox};
+use crate::game::Game;
+use crate::popup::Popup;
+use crate::position::Position;
+use crate::puzzle::PuzzleContext;
+use crate::screen::{self, ScreenScale};
+use crate::settings::{Settings, SettingsOverlay};
+use crate::tutorial::Tutorial;
+use new_game::{NewGameEvent, NewGamePanel};
+use std::any::Any;
+
+// The title screen, and the way between the other screens. It keeps the game, whose board shows dimmed
+// behind the menu: "Resume" goes back to it, and "New game" and "Load" replace it.
+// The puzzles and the tutorial borrow the game for the profiles and the puzzles, and come back here.
+// The settings and the question before quitting are overlays on top of the menu.
+pub struct MainMenu {
+    game: Box<Game>,
+    new_game: Option<NewGamePanel>,  // over the buttons, when it's open
+    buttons: Vec<(Button, MenuAction)>,
+    curr_popup: Popup,
+    screen_scale: Option<ScreenScale>,
+    frame_count: usize,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum MenuAction {
+    Resume,
+    NewGame,
+    Load,
+    Puzzles,
+    Tutorial,
+    Settings,
+    Quit,
+}
+
+impl MainMenu {
+    pub fn new(game: Box<Game>) -> Self {
+        let mut actions = vec![];
+
+        if game.is_in_progress() {
+            actions.push((MenuAction::Resume, "Resume"));
+        }
+
+        actions.extend([
+            (MenuAction::NewGame, "New game"),
+            (MenuAction::Load, "Load"),
+            (MenuAction::Puzzles, "Puzzles"),
+            (MenuAction::Tutorial, "Tutorial"),
+            (MenuAction::Settings, "Settings"),
+            (MenuAction::Quit, "Quit"),
+        ]);
+
+        MainMenu {
+            game,
+            new_game: None,
+            buttons: actions.into_iter().map(|(action, label)| (Button::new(0.0, 0.0, label), action)).collect(),
+            curr_popup: Popup::dummy(),
+            screen_scale: ScreenScale::calc(),
+            frame_count: 0,
+        }
+    }
+
+    fn locate_buttons(&mut self, screen_w: f32, screen_h: f32) {
+        let top = (screen_h - self.buttons.len() as f32 * BUTTON_GAP) / 2.0 + 60.0;
+
+        for (index, (button, _)) in self.buttons.iter_mut().enumerate() {
+            button.move_to((screen_w - 180.0) / 2.0, top + index as f32 * BUTTON_GAP);
+        }
+    }
+
+    // `Some(MenuAction::Resume)` once the new game starts
+    fn update_new_game(&mut self, inputs: &Inputs, mouse_pos: (f32, f32), center_x: f32, center_y: f32) -> Option<MenuAction> {
+        let panel = self.new_game.as_mut()?;
+
+        match panel.update(inputs, mouse_pos, center_x, center_y, self.game.profiles()) {
+            Some(NewGameEvent::Start) => {
+                self.game.start_new_game(&panel.options);
+                self.new_game = None;
+
+                return Some(MenuAction::Resume);
+            },
+            Some(NewGameEvent::Cancel) => { self.new_game = None; },
+            None => {},
+        }
+
+        if inputs.key_pressed[KEY_ESCAPE] {
+            self.new_game = None;
+        }
+
+        None
+    }
+
+    // the screen that the menu opens this frame, if any
+    fn update_buttons(&mut self, inputs: &Inputs, mouse_pos: (f32, f32)) -> Option<MenuAction> {
+        let mut clicked = None;
+
+        for (button, action) in self.buttons.iter_mut() {
+            if button.check_mouse(mouse_pos) && inputs.mouse_pressed[0] {
+                clicked = Some(*action);
+            }
+        }
+
+        if inputs.key_pressed[KEY_ESCAPE] && self.game.is_in_progress() {
+            clicked = Some(MenuAction::Resume);
+        }
+
+        match clicked {
+            Some(MenuAction::NewGame) => {
+                self.new_game = Some(NewGamePanel::new(self.game.new_game_options(), self.game.profiles()));
+                None
+            },
+            Some(MenuAction::Settings) => {
+                global_env().push_context(Box::new(SettingsOverlay::new(self.game.settings())));
+                None
+            },
+            Some(MenuAction::Load) => match self.game.load_game() {
+                Ok(()) => Some(MenuAction::Resume),
+                Err(e) => {
+                    self.curr_popup = Popup::new(&e);
+                    None
+                },
+            },
+            Some(MenuAction::Quit) => {
+                global_env().push_context(Box::new(Confirm::new("Quit the game?", MenuAction::Quit)));
+                None
+            },
+            action => action,
+        }
+    }
+
+    fn render(&mut self, screen_w: f32, screen_h: f32) -> Vec<Graphic> {
+        let (box_x, box_y) = Board::origin(screen_w, screen_h);
+        let position = if self.game.is_in_progress() { Position::from_game(&self.game) } else { Position::new() };
+        let (center_x, center_y) = (screen_w / 2.0, screen_h / 2.0);
+
+        let panel = match &mut self.new_game {
+            Some(panel) => panel.render(center_x, center_y),
+            None => [
+                TextBox::new("QUORIDOR", 0.0, center_y - 300.0, screen_w, 100.0, 72.0).set_color(Color::ui()).align_center().render(),
+                self.buttons.iter_mut().flat_map(|(button, _)| button.render()).collect(),
+            ].concat(),
+        };
+
+        [
+            board::draw_board(&position, box_x, box_y),
+            board::draw_pawns(&position, box_x, box_y),
+            vec![Graphic::new_rect(0.0, 0.0, screen_w, screen_h, 0.0, Color::new(0, 0, 0, 176))],
+            panel,
+            self.curr_popup.render(),
+        ].concat()
+    }
+}
+
+impl Context for MainMenu {
+    fn frame(mut self: Box<Self>, inputs: Inputs) -> (Box<dyn Context>, Vec<Graphic>, Vec<SoundAction>) {
+        self.frame_count += 1;
+
+        if inputs.is_screen_size_changed || self.frame_count & 7 == 7 {
+            self.screen_scale = ScreenScale::calc();
+        }
+
+        let (screen_w, screen_h) = screen::screen_size(&self.screen_scale);
+        let mouse_pos = screen::scale_mouse(&self.screen_scale, inputs.mouse_pos);
+        self.locate_buttons(screen_w, screen_h);
+
+        let action = if self.new_game.is_some() {
+            self.update_new_game(&inputs, mouse_pos, screen_w / 2.0, screen_h / 2.0)
+        } else {
+            self.update_buttons(&inputs, mouse_pos)
+        };
+
+        let graphics = self.render(screen_w, screen_h);
+        let graphics = screen::scale_screen(&self.screen_scale, graphics);
+
+        let next: Box<dyn Context> = match action {
+            Some(MenuAction::Resume) => {
+                let mut game = self.game;
+                game.resume();
+
+                game
+            },
+            Some(MenuAction::Puzzles) => Box::new(PuzzleContext::new(self.game)),
+            Some(MenuAction::Tutorial) => Box::new(Tutorial::new(self.game)),
+            _ => { return (self, graphics, vec![]); },
+        };
+
+        transition::fade(next, graphics)
+    }
+
+    fn uncover(&mut self, result: Option<Box<dyn Any>>) {
+        let result = match result {
+            Some(result) => result,
+            None => { return; },
+        };
+
+        if let Some(settings) = result.downcast_ref::<Settings>() {
+            self.game.apply_settings(settings);
+        }
+
+        else if result.downcast_ref::<MenuAction>() == Some(&MenuAction::Quit) {
+            global_env().quit();
+        }
+    }
+}
+
+const BUTTON_GAP: f32 = 60.0;
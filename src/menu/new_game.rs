@@ -0,0 +1,158 @@
+use crate::ai::personality::Personality;
+use crate::engine::color::Color;
+use crate::engine::graphic::Graphic;
+use crate::engine::inputs::Inputs;
+use crate::engine::widget::{button::Button, textbox::TextBox};
+use crate::game::{level_label, personality_label, time_control_label, TIME_CONTROLS};
+use crate::profile::Profile;
+
+// the choices of "New game" in the main menu
+#[derive(Clone)]
+pub struct NewGame {
+    pub mode: Mode,
+    pub profile: Option<usize>,  // of the human who plays player 1, `None` for a guest
+    pub personality: Personality,
+    pub adaptive: bool,
+    pub time_control: Option<usize>,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Mode {
+    VsCpu,
+    VsHuman,
+    Watch,  // cpu vs cpu
+}
+
+impl Mode {
+    fn next(self) -> Self {
+        match self {
+            Mode::VsCpu => Mode::VsHuman,
+            Mode::VsHuman => Mode::Watch,
+            Mode::Watch => Mode::VsCpu,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Mode::VsCpu => "vs CPU",
+            Mode::VsHuman => "vs HUMAN",
+            Mode::Watch => "CPU vs CPU",
+        }
+    }
+}
+
+// The panel of "New game". The rows that don't matter for the mode are hidden: the player for a cpu game,
+// and the cpu for a game between humans. The handicap and the matches are in the dialog of the game.
+pub struct NewGamePanel {
+    pub options: NewGame,
+    buttons: Vec<(Button, Choice)>,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Choice {
+    Mode,
+    Profile,
+    Personality,
+    Level,
+    TimeControl,
+    Start,
+    Cancel,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum NewGameEvent {
+    Start,
+    Cancel,
+}
+
+impl NewGamePanel {
+    pub fn new(options: NewGame, profiles: &[Profile]) -> Self {
+        let mut panel = NewGamePanel {
+            options,
+            buttons: vec![],
+        };
+
+        panel.label_buttons(profiles);
+        panel
+    }
+
+    // the labels show the current choices, so the buttons are built again after every click
+    fn label_buttons(&mut self, profiles: &[Profile]) {
+        let options = &self.options;
+        let mut rows = vec![(format!("Mode: {}", options.mode.name()), Choice::Mode)];
+
+        if options.mode != Mode::Watch {
+            let name = options.profile.map(|index| profiles[index].name.as_str()).unwrap_or("guest");
+            rows.push((format!("Player: {}", name), Choice::Profile));
+        }
+
+        if options.mode != Mode::VsHuman {
+            rows.push((personality_label(options.personality), Choice::Personality));
+            rows.push((level_label(options.adaptive), Choice::Level));
+        }
+
+        rows.push((time_control_label(options.time_control), Choice::TimeControl));
+        rows.push((String::from("Start"), Choice::Start));
+        rows.push((String::from("Cancel"), Choice::Cancel));
+
+        self.buttons = rows.iter().map(|(label, choice)| (Button::new(0.0, 0.0, label), *choice)).collect();
+    }
+
+    // the panel is centered at (center_x, center_y)
+    pub fn update(&mut self, inputs: &Inputs, mouse_pos: (f32, f32), center_x: f32, center_y: f32, profiles: &[Profile]) -> Option<NewGameEvent> {
+        let (panel_x, panel_y, _, _) = panel_rect(center_x, center_y);
+        let mut clicked = None;
+
+        for (index, (button, choice)) in self.buttons.iter_mut().enumerate() {
+            button.move_to(panel_x + (PANEL_W - 180.0) / 2.0, panel_y + ROW_Y + index as f32 * ROW_GAP);
+
+            if button.check_mouse(mouse_pos) && inputs.mouse_pressed[0] {
+                clicked = Some(*choice);
+            }
+        }
+
+        let options = &mut self.options;
+
+        match clicked {
+            Some(Choice::Mode) => { options.mode = options.mode.next(); },
+            Some(Choice::Profile) => {
+                options.profile = match options.profile {
+                    None if !profiles.is_empty() => Some(0),
+                    Some(index) if index + 1 < profiles.len() => Some(index + 1),
+                    _ => None,
+                };
+            },
+            Some(Choice::Personality) => { options.personality = options.personality.next(); },
+            Some(Choice::Level) => { options.adaptive = !options.adaptive; },
+            Some(Choice::TimeControl) => {
+                let index = TIME_CONTROLS.iter().position(|seconds| *seconds == options.time_control).unwrap_or(0);
+                options.time_control = TIME_CONTROLS[(index + 1) % TIME_CONTROLS.len()];
+            },
+            Some(Choice::Start) => { return Some(NewGameEvent::Start); },
+            Some(Choice::Cancel) => { return Some(NewGameEvent::Cancel); },
+            None => { return None; },
+        }
+
+        self.label_buttons(profiles);
+        None
+    }
+
+    pub fn render(&mut self, center_x: f32, center_y: f32) -> Vec<Graphic> {
+        let (panel_x, panel_y, panel_w, panel_h) = panel_rect(center_x, center_y);
+
+        [
+            vec![Graphic::new_round_rect(panel_x, panel_y, panel_w, panel_h, 16.0, 0.0, Color::new(16, 16, 16, 224))],
+            TextBox::new("New game", panel_x, panel_y + 20.0, panel_w, 50.0, 32.0).set_color(Color::ui()).align_center().render(),
+            self.buttons.iter_mut().flat_map(|(button, _)| button.render()).collect(),
+        ].concat()
+    }
+}
+
+fn panel_rect(center_x: f32, center_y: f32) -> (f32, f32, f32, f32) {
+    (center_x - PANEL_W / 2.0, center_y - PANEL_H / 2.0, PANEL_W, PANEL_H)
+}
+
+const PANEL_W: f32 = 300.0;
+const PANEL_H: f32 = 480.0;
+const ROW_Y: f32 = 90.0;
+const ROW_GAP: f32 = 50.0;
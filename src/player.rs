@@ -20,6 +20,16 @@ impl Player {
         }
     }
 
+    // away from home, or with fewer walls, for a handicap
+    pub fn starting_at(is_player1: bool, position: (i32, i32), walls: usize) -> Self {
+        let mut player = Player::new(is_player1);
+        player.walls = walls;
+        player.position = position;
+        player.trace = vec![position];
+
+        player
+    }
+
     pub fn move_to(&mut self, x: i32, y: i32) {
         self.position = (x, y);
         self.trace.push(self.position);
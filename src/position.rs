@@ -0,0 +1,598 @@
+use crate::game::Game;
+
+// A compact copy of the board that the AI can clone, hash and send to other threads.
+// The indices are the same as `Game`'s: `vertical_walls[x][y]`, `horizontal_walls[y][x]` and `cross_walls[x][y]`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Position {
+    pub player1: (i32, i32),
+    pub player2: (i32, i32),
+    pub walls: (usize, usize),  // (player1, player2)
+    pub vertical_walls: [[bool; 9]; 10],
+    pub horizontal_walls: [[bool; 9]; 10],
+    pub cross_walls: [[bool; 10]; 10],
+    pub player1_turn: bool,
+    hash: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Move {
+    Step(i32, i32),
+    Vertical(usize, usize),
+    Horizontal(usize, usize),
+}
+
+impl Position {
+    pub fn new() -> Self {
+        let mut position = Position {
+            player1: (0, 4),
+            player2: (8, 4),
+            walls: (10, 10),
+            vertical_walls: [[false; 9]; 10],
+            horizontal_walls: [[false; 9]; 10],
+            cross_walls: [[false; 10]; 10],
+            player1_turn: true,
+            hash: 0,
+        };
+
+        position.hash = position.calc_hash();
+        position
+    }
+
+    pub fn from_game(game: &Game) -> Self {
+        let mut position = Position::new();
+        position.player1 = game.player1.position;
+        position.player2 = game.player2.position;
+        position.walls = (game.player1.walls, game.player2.walls);
+        position.player1_turn = game.player1_turn;
+
+        for x in 0..10 {
+            for y in 0..9 {
+                position.vertical_walls[x][y] = game.vertical_walls[x][y];
+                position.horizontal_walls[x][y] = game.horizontal_walls[x][y];
+            }
+
+            for y in 0..10 {
+                position.cross_walls[x][y] = game.cross_walls[x][y];
+            }
+        }
+
+        position.hash = position.calc_hash();
+        position
+    }
+
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    // after the fields were changed directly, like for a handicap
+    pub fn update_hash(&mut self) {
+        self.hash = self.calc_hash();
+    }
+
+    fn calc_hash(&self) -> u64 {
+        let mut hash = zobrist_player(true, self.player1) ^ zobrist_player(false, self.player2)
+            ^ ZOBRIST[ZOBRIST_WALLS_IN_HAND + self.walls.0.min(10)]
+            ^ ZOBRIST[ZOBRIST_WALLS_IN_HAND + 11 + self.walls.1.min(10)];
+
+        if !self.player1_turn {
+            hash ^= ZOBRIST[ZOBRIST_TURN];
+        }
+
+        for x in 0..10 {
+            for y in 0..9 {
+                if self.vertical_walls[x][y] {
+                    hash ^= ZOBRIST[ZOBRIST_VERTICAL + x * 9 + y];
+                }
+
+                if self.horizontal_walls[x][y] {
+                    hash ^= ZOBRIST[ZOBRIST_HORIZONTAL + x * 9 + y];
+                }
+            }
+
+            for y in 0..10 {
+                if self.cross_walls[x][y] {
+                    hash ^= ZOBRIST[ZOBRIST_CROSS + x * 10 + y];
+                }
+            }
+        }
+
+        hash
+    }
+
+    pub fn curr_walls(&self) -> usize {
+        if self.player1_turn { self.walls.0 } else { self.walls.1 }
+    }
+
+    // `Some(true)` if player 1 made it, `Some(false)` if player 2 did
+    pub fn winner(&self) -> Option<bool> {
+        if self.player1.0 == 8 {
+            Some(true)
+        }
+
+        else if self.player2.0 == 0 {
+            Some(false)
+        }
+
+        else {
+            None
+        }
+    }
+
+    // same as `Game::is_movable_at`, but it only takes unit directions
+    pub fn is_movable_at(&self, pos: (i32, i32), dir: (i32, i32)) -> bool {
+        match dir {
+            (0, -1) => !self.horizontal_walls[pos.1 as usize][pos.0 as usize],
+            (0, 1) => !self.horizontal_walls[pos.1 as usize + 1][pos.0 as usize],
+            (-1, 0) => !self.vertical_walls[pos.0 as usize][pos.1 as usize],
+            (1, 0) => !self.vertical_walls[pos.0 as usize + 1][pos.1 as usize],
+            _ => false,
+        }
+    }
+
+    // same rule as `Game::get_valid_moves`
+    pub fn valid_steps(&self) -> Vec<(i32, i32)> {
+        self.steps_of(self.player1_turn)
+    }
+
+    // where the pawn could go if it were its turn
+    pub fn steps_of(&self, player1: bool) -> Vec<(i32, i32)> {
+        let mut result = Vec::with_capacity(4);
+
+        let ((x, y), (another_x, another_y)) = if player1 {
+            (self.player1, self.player2)
+        } else {
+            (self.player2, self.player1)
+        };
+
+        let mut possible_moves = vec![(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+        while let Some((dx, dy)) = possible_moves.pop() {
+            let (new_x, new_y) = (x + dx, y + dy);
+
+            if !(0..9).contains(&new_x) || !(0..9).contains(&new_y) {
+                continue;
+            }
+
+            let unit = (dx.signum(), dy.signum());
+            let is_blocked = if dx.abs() == 2 || dy.abs() == 2 {
+                !self.is_movable_at((x, y), unit) || !self.is_movable_at((x + unit.0, y + unit.1), unit)
+            } else {
+                !self.is_movable_at((x, y), unit)
+            };
+
+            if is_blocked {}
+
+            else if (new_x, new_y) == (another_x, another_y) {
+                possible_moves.push((dx * 2, dy * 2));
+            }
+
+            else {
+                result.push((new_x, new_y));
+            }
+        }
+
+        result
+    }
+
+    pub fn is_vertical_wall_ok_at(&self, x: usize, y: usize) -> bool {
+        (x < 9 && y < 8 && x > 0)
+        && !(self.vertical_walls[x][y] || self.vertical_walls[x][y + 1])
+        && !self.cross_walls[x][y + 1]
+    }
+
+    pub fn is_horizontal_wall_ok_at(&self, x: usize, y: usize) -> bool {
+        (y < 9 && x < 8 && y > 0)
+        && !(self.horizontal_walls[y][x] || self.horizontal_walls[y][x + 1])
+        && !self.cross_walls[x + 1][y]
+    }
+
+    // length of the shortest path to the goal, ignoring the pawns
+    // `None` if the player is trapped
+    pub fn distance(&self, player1: bool) -> Option<u8> {
+        let (start, destination) = if player1 { (self.player1, 8) } else { (self.player2, 0) };
+        let mut distances = [[u8::MAX; 9]; 9];
+        let mut queue = [(0, 0); 81];
+        let (mut head, mut tail) = (0, 1);
+        queue[0] = start;
+        distances[start.0 as usize][start.1 as usize] = 0;
+
+        while head < tail {
+            let (x, y) = queue[head];
+            head += 1;
+            let curr_distance = distances[x as usize][y as usize];
+
+            if x == destination {
+                return Some(curr_distance);
+            }
+
+            for (dx, dy) in DIRECTIONS.iter() {
+                let (new_x, new_y) = (x + dx, y + dy);
+
+                if (0..9).contains(&new_x) && (0..9).contains(&new_y)
+                    && distances[new_x as usize][new_y as usize] == u8::MAX
+                    && self.is_movable_at((x, y), (*dx, *dy)) {
+                    distances[new_x as usize][new_y as usize] = curr_distance + 1;
+                    queue[tail] = (new_x, new_y);
+                    tail += 1;
+                }
+            }
+        }
+
+        None
+    }
+
+    // cells of the shortest path to the goal, including the starting cell
+    pub fn shortest_path(&self, player1: bool) -> Option<Vec<(i32, i32)>> {
+        let (start, destination) = if player1 { (self.player1, 8) } else { (self.player2, 0) };
+        let mut parents = [[None; 9]; 9];
+        let mut visited = [[false; 9]; 9];
+        let mut queue = [(0, 0); 81];
+        let (mut head, mut tail) = (0, 1);
+        queue[0] = start;
+        visited[start.0 as usize][start.1 as usize] = true;
+
+        while head < tail {
+            let (x, y) = queue[head];
+            head += 1;
+
+            if x == destination {
+                let mut path = vec![(x, y)];
+                let mut curr = (x, y);
+
+                while let Some(parent) = parents[curr.0 as usize][curr.1 as usize] {
+                    path.push(parent);
+                    curr = parent;
+                }
+
+                path.reverse();
+                return Some(path);
+            }
+
+            for (dx, dy) in DIRECTIONS.iter() {
+                let (new_x, new_y) = (x + dx, y + dy);
+
+                if (0..9).contains(&new_x) && (0..9).contains(&new_y)
+                    && !visited[new_x as usize][new_y as usize]
+                    && self.is_movable_at((x, y), (*dx, *dy)) {
+                    visited[new_x as usize][new_y as usize] = true;
+                    parents[new_x as usize][new_y as usize] = Some((x, y));
+                    queue[tail] = (new_x, new_y);
+                    tail += 1;
+                }
+            }
+        }
+
+        None
+    }
+
+    // it does not check whether the wall traps a player
+    pub fn is_wall_ok(&self, mv: Move) -> bool {
+        match mv {
+            Move::Vertical(x, y) => self.is_vertical_wall_ok_at(x, y),
+            Move::Horizontal(x, y) => self.is_horizontal_wall_ok_at(x, y),
+            Move::Step(_, _) => false,
+        }
+    }
+
+    pub fn is_legal(&self, mv: Move) -> bool {
+        if self.winner().is_some() {
+            return false;
+        }
+
+        match mv {
+            Move::Step(x, y) => self.valid_steps().contains(&(x, y)),
+            _ => {
+                if self.curr_walls() == 0 || !self.is_wall_ok(mv) {
+                    return false;
+                }
+
+                let mut next = self.clone();
+                next.apply(mv);
+                next.distance(true).is_some() && next.distance(false).is_some()
+            },
+        }
+    }
+
+    // pawn steps first, then every wall that does not trap anyone
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if self.winner().is_some() {
+            return vec![];
+        }
+
+        let mut result: Vec<Move> = self.valid_steps().into_iter().map(|(x, y)| Move::Step(x, y)).collect();
+
+        if self.curr_walls() > 0 {
+            for (mv, _, _) in self.wall_candidates(false) {
+                result.push(mv);
+            }
+        }
+
+        result
+    }
+
+    // every legal wall with the new path lengths of (player1, player2)
+    // if `only_touching` is set, it skips the walls that do not touch any shortest path
+    pub fn wall_candidates(&self, only_touching: bool) -> Vec<(Move, u8, u8)> {
+        let mut result = Vec::with_capacity(128);
+
+        let (path1, path2) = match (self.shortest_path(true), self.shortest_path(false)) {
+            (Some(path1), Some(path2)) => (path1, path2),
+            _ => { return result; },
+        };
+        let (distance1, distance2) = ((path1.len() - 1) as u8, (path2.len() - 1) as u8);
+
+        // `crossings[x][y]` for vertical lines and `crossings[10 + y][x]` for horizontal lines
+        let mut crossings = [[false; 10]; 20];
+
+        for path in [&path1, &path2] {
+            for step in path.windows(2) {
+                let ((x1, y1), (x2, y2)) = (step[0], step[1]);
+
+                if y1 == y2 {
+                    crossings[x1.max(x2) as usize][y1 as usize] = true;
+                }
+
+                else {
+                    crossings[10 + y1.max(y2) as usize][x1 as usize] = true;
+                }
+            }
+        }
+
+        for index in 81..MOVE_INDEX_COUNT as u16 {
+            let mv = match Move::from_index(index) {
+                Some(mv) if self.is_wall_ok(mv) => mv,
+                _ => { continue; },
+            };
+            let touches_path = match mv {
+                Move::Vertical(x, y) => crossings[x][y] || crossings[x][y + 1],
+                Move::Horizontal(x, y) => crossings[10 + y][x] || crossings[10 + y][x + 1],
+                Move::Step(_, _) => false,
+            };
+
+            if touches_path {
+                if let Some((d1, d2)) = self.distances_after(mv) {
+                    result.push((mv, d1, d2));
+                }
+            }
+
+            else if !only_touching {
+                result.push((mv, distance1, distance2));
+            }
+        }
+
+        result
+    }
+
+    fn distances_after(&self, wall: Move) -> Option<(u8, u8)> {
+        let mut next = self.clone();
+        next.apply(wall);
+
+        match (next.distance(true), next.distance(false)) {
+            (Some(d1), Some(d2)) => Some((d1, d2)),
+            _ => None,
+        }
+    }
+
+    // it does not check the legality of the move
+    pub fn apply(&mut self, mv: Move) {
+        match mv {
+            Move::Step(x, y) => {
+                if self.player1_turn {
+                    self.hash ^= zobrist_player(true, self.player1) ^ zobrist_player(true, (x, y));
+                    self.player1 = (x, y);
+                }
+
+                else {
+                    self.hash ^= zobrist_player(false, self.player2) ^ zobrist_player(false, (x, y));
+                    self.player2 = (x, y);
+                }
+            },
+            Move::Vertical(x, y) => {
+                self.vertical_walls[x][y] = true;
+                self.vertical_walls[x][y + 1] = true;
+                self.cross_walls[x][y + 1] = true;
+                self.hash ^= ZOBRIST[ZOBRIST_VERTICAL + x * 9 + y]
+                    ^ ZOBRIST[ZOBRIST_VERTICAL + x * 9 + y + 1]
+                    ^ ZOBRIST[ZOBRIST_CROSS + x * 10 + y + 1];
+                self.use_wall();
+            },
+            Move::Horizontal(x, y) => {
+                self.horizontal_walls[y][x] = true;
+                self.horizontal_walls[y][x + 1] = true;
+                self.cross_walls[x + 1][y] = true;
+                self.hash ^= ZOBRIST[ZOBRIST_HORIZONTAL + y * 9 + x]
+                    ^ ZOBRIST[ZOBRIST_HORIZONTAL + y * 9 + x + 1]
+                    ^ ZOBRIST[ZOBRIST_CROSS + (x + 1) * 10 + y];
+                self.use_wall();
+            },
+        }
+
+        self.player1_turn = !self.player1_turn;
+        self.hash ^= ZOBRIST[ZOBRIST_TURN];
+    }
+
+    fn use_wall(&mut self) {
+        let (walls, offset) = if self.player1_turn {
+            (&mut self.walls.0, ZOBRIST_WALLS_IN_HAND)
+        } else {
+            (&mut self.walls.1, ZOBRIST_WALLS_IN_HAND + 11)
+        };
+
+        self.hash ^= ZOBRIST[offset + (*walls).min(10)];
+        *walls = walls.saturating_sub(1);
+        self.hash ^= ZOBRIST[offset + (*walls).min(10)];
+    }
+}
+
+impl Move {
+    // squares are `a1` ~ `i9`: the letter is the x index, and the number is the y index plus one
+    // a wall is written as the square on the upper left of its center, followed by `v` or `h`
+    pub fn to_notation(self) -> String {
+        let square = |x: i32, y: i32| format!("{}{}", (b'a' + x as u8) as char, y + 1);
+
+        match self {
+            Move::Step(x, y) => square(x, y),
+            Move::Vertical(x, y) => format!("{}v", square(x as i32 - 1, y as i32)),
+            Move::Horizontal(x, y) => format!("{}h", square(x as i32, y as i32 - 1)),
+        }
+    }
+
+    pub fn from_notation(notation: &str) -> Option<Move> {
+        let chars: Vec<char> = notation.trim().chars().collect();
+
+        if chars.len() < 2 || chars.len() > 3 || !('a'..='i').contains(&chars[0]) || !('1'..='9').contains(&chars[1]) {
+            return None;
+        }
+
+        let (x, y) = (chars[0] as usize - 'a' as usize, chars[1] as usize - '1' as usize);
+
+        let mv = match chars.get(2) {
+            None => Move::Step(x as i32, y as i32),
+            Some('v') => Move::Vertical(x + 1, y),
+            Some('h') => Move::Horizontal(x, y + 1),
+            _ => { return None; },
+        };
+
+        // `i9v` and the like
+        if mv.to_index() as usize >= MOVE_INDEX_COUNT || Move::from_index(mv.to_index()) != Some(mv) {
+            return None;
+        }
+
+        Some(mv)
+    }
+
+    // a dense index for transposition tables and move lists, `0..MOVE_INDEX_COUNT`
+    pub fn to_index(self) -> u16 {
+        match self {
+            Move::Step(x, y) => (y * 9 + x) as u16,
+            Move::Vertical(x, y) => (81 + (x - 1) * 8 + y) as u16,
+            Move::Horizontal(x, y) => (145 + x * 8 + y - 1) as u16,
+        }
+    }
+
+    pub fn from_index(index: u16) -> Option<Move> {
+        let index = index as usize;
+
+        if index < 81 {
+            Some(Move::Step((index % 9) as i32, (index / 9) as i32))
+        }
+
+        else if index < 145 {
+            Some(Move::Vertical((index - 81) / 8 + 1, (index - 81) % 8))
+        }
+
+        else if index < MOVE_INDEX_COUNT {
+            Some(Move::Horizontal((index - 145) / 8, (index - 145) % 8 + 1))
+        }
+
+        else {
+            None
+        }
+    }
+}
+
+pub const MOVE_INDEX_COUNT: usize = 209;
+
+const DIRECTIONS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+const ZOBRIST_VERTICAL: usize = 0;
+const ZOBRIST_HORIZONTAL: usize = 90;
+const ZOBRIST_CROSS: usize = 180;
+const ZOBRIST_PLAYER1: usize = 280;
+const ZOBRIST_PLAYER2: usize = 361;
+const ZOBRIST_WALLS_IN_HAND: usize = 442;
+const ZOBRIST_TURN: usize = 464;
+const ZOBRIST_LEN: usize = 465;
+
+static ZOBRIST: [u64; ZOBRIST_LEN] = zobrist_keys();
+
+fn zobrist_player(player1: bool, (x, y): (i32, i32)) -> u64 {
+    let offset = if player1 { ZOBRIST_PLAYER1 } else { ZOBRIST_PLAYER2 };
+    ZOBRIST[offset + (x * 9 + y) as usize]
+}
+
+// splitmix64, so that the keys are the same on every build
+const fn zobrist_keys() -> [u64; ZOBRIST_LEN] {
+    let mut keys = [0; ZOBRIST_LEN];
+    let mut state: u64 = 0x5155_4f52_4944_4f52;
+    let mut i = 0;
+
+    while i < ZOBRIST_LEN {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(moves: &[Move]) -> Position {
+        let mut position = Position::new();
+
+        for mv in moves {
+            position.apply(*mv);
+        }
+
+        position
+    }
+
+    #[test]
+    fn move_index_round_trip() {
+        for index in 0..MOVE_INDEX_COUNT as u16 {
+            assert_eq!(Move::from_index(index).unwrap().to_index(), index);
+        }
+
+        assert_eq!(Move::from_index(MOVE_INDEX_COUNT as u16), None);
+    }
+
+    #[test]
+    fn incremental_hash_matches_fresh_hash() {
+        let mut position = Position::new();
+
+        for ply in 0..60 {
+            if position.winner().is_some() {
+                break;
+            }
+
+            // a search undoes a move by going back to its copy of the position
+            let moves = position.legal_moves();
+            let before = position.clone();
+            position.apply(moves[ply * 7 % moves.len()]);
+
+            assert_eq!(position.hash(), position.calc_hash());
+            assert_ne!(position.hash(), before.hash());
+            assert_eq!(before.hash(), before.calc_hash());
+        }
+    }
+
+    #[test]
+    fn transposed_moves_hash_the_same() {
+        let (step1, step2) = (Move::Step(1, 4), Move::Step(7, 4));
+        let (wall1, wall2) = (Move::Horizontal(2, 3), Move::Horizontal(5, 6));
+        let a = play(&[step1, step2, wall1, wall2]);
+        let b = play(&[wall1, wall2, step1, step2]);
+
+        assert!(a == b);
+        assert_eq!(a.hash(), b.hash());
+        assert_ne!(a.hash(), play(&[step1, step2, wall1]).hash());
+    }
+
+    #[test]
+    fn notation_round_trip() {
+        for index in 0..MOVE_INDEX_COUNT as u16 {
+            let mv = Move::from_index(index).unwrap();
+            assert_eq!(Move::from_notation(&mv.to_notation()), Some(mv));
+        }
+
+        assert_eq!(Move::from_notation("i9v"), None);
+        assert_eq!(Move::from_notation("a9h"), None);
+        assert_eq!(Move::from_notation("j1"), None);
+    }
+}
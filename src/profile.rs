@@ -0,0 +1,192 @@
+use crate::engine::color::Color;
+use crate::engine::file_io::{create_dir, read_dir, read_string, write_to_file};
+
+// A local player, saved as `PROFILES_DIR/<name>.txt`:
+//
+// # Quoridor profile
+// name: alice
+// color: 64 160 224
+// vs cpu: 3 5 1 412 61
+// vs racer cpu lv3: 1 0 0 58 7
+// vs human: 2 2 0 330 40
+// solved: race-1 wall-2
+//
+// A `vs` line is the record against one kind of opponent: wins, losses, draws, total moves of the games
+// (both players' moves) and walls that this player placed. The draws are optional, for the older profiles.
+// `solved` lists the ids of the puzzles that this player solved.
+#[derive(Clone)]
+pub struct Profile {
+    pub name: String,
+    pub color: (u8, u8, u8),
+    pub records: Vec<(String, Record)>,  // (opponent, record)
+    pub solved: Vec<String>,  // puzzle ids
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct Record {
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+    pub moves: usize,
+    pub walls: usize,
+}
+
+impl Record {
+    pub fn games(&self) -> usize {
+        self.wins + self.losses + self.draws
+    }
+
+    pub fn average_length(&self) -> f32 {
+        self.moves as f32 / self.games().max(1) as f32
+    }
+
+    pub fn walls_per_game(&self) -> f32 {
+        self.walls as f32 / self.games().max(1) as f32
+    }
+}
+
+impl Profile {
+    pub fn new(name: &str, color: (u8, u8, u8)) -> Self {
+        Profile {
+            name: name.to_string(),
+            color,
+            records: vec![],
+            solved: vec![],
+        }
+    }
+
+    pub fn avatar_color(&self) -> Color {
+        Color::new(self.color.0, self.color.1, self.color.2, 255)
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut profile = Profile::new("", DEFAULT_COLOR);
+
+        for line in s.lines() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => { return Err(format!("Invalid line in a profile: `{}`", line)); },
+            };
+
+            if key == "name" {
+                profile.name = value.to_string();
+            }
+
+            else if key == "color" {
+                profile.color = match parse_numbers(value)[..] {
+                    [r, g, b] if r < 256 && g < 256 && b < 256 => (r as u8, g as u8, b as u8),
+                    _ => { return Err(format!("Invalid color in a profile: `{}`", value)); },
+                };
+            }
+
+            else if let Some(opponent) = key.strip_prefix("vs ") {
+                let record = match parse_numbers(value)[..] {
+                    [wins, losses, moves, walls] => Record { wins, losses, draws: 0, moves, walls },
+                    [wins, losses, draws, moves, walls] => Record { wins, losses, draws, moves, walls },
+                    _ => { return Err(format!("Invalid record in a profile: `{}`", line)); },
+                };
+
+                profile.records.push((opponent.trim().to_string(), record));
+            }
+
+            else if key == "solved" {
+                profile.solved = value.split_whitespace().map(|id| id.to_string()).collect();
+            }
+        }
+
+        if profile.name.is_empty() {
+            return Err(String::from("A profile without a name"));
+        }
+
+        Ok(profile)
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut lines = vec![
+            String::from("# Quoridor profile"),
+            format!("name: {}", self.name),
+            format!("color: {} {} {}", self.color.0, self.color.1, self.color.2),
+        ];
+
+        for (opponent, record) in self.records.iter() {
+            lines.push(format!("vs {}: {} {} {} {} {}", opponent, record.wins, record.losses, record.draws, record.moves, record.walls));
+        }
+
+        if !self.solved.is_empty() {
+            lines.push(format!("solved: {}", self.solved.join(" ")));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        create_dir(PROFILES_DIR).map_err(|_| format!("Failed to create directory: {}", PROFILES_DIR))?;
+
+        let path = profile_path(&self.name);
+        write_to_file(&path, self.serialize().as_bytes()).map_err(|_| format!("Failed to write file: {}", path))
+    }
+
+    // after a game against `opponent`: `won` is `None` for a draw
+    pub fn add_game(&mut self, opponent: &str, won: Option<bool>, moves: usize, walls: usize) {
+        let index = match self.records.iter().position(|(name, _)| name == opponent) {
+            Some(index) => index,
+            None => {
+                self.records.push((opponent.to_string(), Record::default()));
+                self.records.len() - 1
+            },
+        };
+        let record = &mut self.records[index].1;
+
+        match won {
+            Some(true) => { record.wins += 1; },
+            Some(false) => { record.losses += 1; },
+            None => { record.draws += 1; },
+        }
+
+        record.moves += moves;
+        record.walls += walls;
+    }
+
+    // `false` if it was solved before
+    pub fn add_solved(&mut self, puzzle_id: &str) -> bool {
+        if self.solved.iter().any(|id| id == puzzle_id) {
+            return false;
+        }
+
+        self.solved.push(puzzle_id.to_string());
+        true
+    }
+}
+
+// every profile in `PROFILES_DIR`, sorted by name; the broken ones are skipped
+pub fn load_profiles() -> Vec<Profile> {
+    let mut result: Vec<Profile> = read_dir(PROFILES_DIR).unwrap_or_default().iter().filter_map(
+        |path| read_string(path).ok().and_then(|s| Profile::parse(&s).ok())
+    ).collect();
+
+    result.sort_by(|a, b| a.name.cmp(&b.name));
+    result
+}
+
+// the names become file names, so only letters, digits, `-` and `_` are allowed
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.len() <= MAX_NAME_LENGTH && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn profile_path(name: &str) -> String {
+    format!("{}/{}.txt", PROFILES_DIR, name)
+}
+
+fn parse_numbers(s: &str) -> Vec<usize> {
+    s.split_whitespace().map_while(|n| n.parse().ok()).collect()
+}
+
+const PROFILES_DIR: &str = "profiles";
+const MAX_NAME_LENGTH: usize = 16;
+pub const DEFAULT_COLOR: (u8, u8, u8) = (224, 224, 224);
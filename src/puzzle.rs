@@ -0,0 +1,486 @@
+mod context;
+pub mod generator;
+
+use crate::ai::endgame::{Outcome, Solver};
+use crate::ai::eval::{Weights, PARAMS_PATH};
+use crate::ai::search::{search, SearchConfig};
+use crate::ai::tt::TranspositionTable;
+use crate::engine::file_io::{read_string, write_to_file};
+use crate::position::{Move, Position};
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+pub use context::PuzzleContext;
+
+// An endgame with a goal for the player to move. The bundled ones are in `puzzles.txt`:
+//
+// # Quoridor puzzles
+// id: race-1
+// goal: race
+// player1: f5 0
+// player2: c5 0
+// walls: f4v e2h
+// turn: 1
+// hint: Jump over the other pawn
+// difficulty: 2
+//
+// A blank line ends a puzzle. `goal` is `race` (win the race), `wall` (place one wall so that you win)
+// or `survive N` (do not lose within the next N moves). `player1` and `player2` are the square of the pawn
+// and the walls in hand, and `walls` are the walls on the board. `difficulty` is the search depth that finds
+// the solution, see `generator::difficulty`. `walls`, `turn`, `hint` and `difficulty` are optional.
+// The walls in hand are few, so that the endgame solver can check every move.
+// The generated puzzles are saved in `PUZZLES_PATH`, in the same format.
+#[derive(Clone)]
+pub struct Puzzle {
+    pub id: String,
+    pub goal: Goal,
+    pub player1: ((i32, i32), usize),  // (square, walls in hand)
+    pub player2: ((i32, i32), usize),
+    pub walls: Vec<Move>,
+    pub player1_turn: bool,
+    pub hint: String,  // empty if there's none
+    pub difficulty: Option<u8>,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Goal {
+    Race,
+    Wall,
+    Survive(u8),  // the opponent's moves
+}
+
+impl Goal {
+    pub fn name(&self) -> String {
+        match self {
+            Goal::Race => String::from("race"),
+            Goal::Wall => String::from("wall"),
+            Goal::Survive(moves) => format!("survive {}", moves),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.split_whitespace().collect::<Vec<&str>>()[..] {
+            ["race"] => Some(Goal::Race),
+            ["wall"] => Some(Goal::Wall),
+            ["survive", moves] => moves.parse().ok().filter(|moves| *moves > 0).map(Goal::Survive),
+            _ => None,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Goal::Race => String::from("Win the race"),
+            Goal::Wall => String::from("Place one wall so that you win the race"),
+            Goal::Survive(moves) => format!("Do not lose in the next {} moves", moves),
+        }
+    }
+}
+
+impl Puzzle {
+    // it checks that the walls fit and that nobody is trapped or has already won
+    pub fn position(&self) -> Result<Position, String> {
+        let mut position = Position::new();
+        position.player1 = self.player1.0;
+        position.player2 = self.player2.0;
+
+        if position.player1 == position.player2 {
+            return Err(format!("Both pawns on one square in the puzzle `{}`", self.id));
+        }
+
+        for wall in self.walls.iter() {
+            if !position.is_wall_ok(*wall) {
+                return Err(format!("Overlapping wall in the puzzle `{}`: `{}`", self.id, wall.to_notation()));
+            }
+
+            position.apply(*wall);
+        }
+
+        position.walls = (self.player1.1, self.player2.1);
+        position.player1_turn = self.player1_turn;
+        position.update_hash();
+
+        if position.distance(true).is_none() || position.distance(false).is_none() {
+            return Err(format!("A trapped pawn in the puzzle `{}`", self.id));
+        }
+
+        if position.winner().is_some() {
+            return Err(format!("The puzzle `{}` is already over", self.id));
+        }
+
+        Ok(position)
+    }
+
+    // The moves that reach the goal, `None` if the solver could not judge every move.
+    // A good puzzle has exactly one.
+    pub fn solutions(&self) -> Option<Vec<Move>> {
+        let start = self.position().ok()?;
+        let mut result = vec![];
+
+        for mv in start.legal_moves() {
+            match Attempt::new(self, start.clone()).judge(mv) {
+                Judgement::Solved | Judgement::Good(_) => { result.push(mv); },
+                Judgement::Failed(_) => {},
+                Judgement::Unknown => { return None; },
+            }
+        }
+
+        Some(result)
+    }
+}
+
+// parses a whole file of puzzles
+pub fn parse_puzzles(s: &str) -> Result<Vec<Puzzle>, String> {
+    let mut result = vec![];
+    let mut lines = vec![];
+
+    // a blank line after the last puzzle ends it
+    for line in s.lines().chain(std::iter::once("")) {
+        let line = line.trim();
+
+        if line.starts_with('#') {
+            continue;
+        }
+
+        if !line.is_empty() {
+            lines.push(line);
+        }
+
+        else if !lines.is_empty() {
+            result.push(parse_puzzle(&lines)?);
+            lines.clear();
+        }
+    }
+
+    Ok(result)
+}
+
+fn parse_puzzle(lines: &[&str]) -> Result<Puzzle, String> {
+    let mut id = None;
+    let mut goal = None;
+    let mut player1 = None;
+    let mut player2 = None;
+    let mut walls = vec![];
+    let mut player1_turn = true;
+    let mut hint = String::new();
+    let mut difficulty = None;
+
+    for line in lines.iter() {
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => { return Err(format!("Invalid line in a puzzle: `{}`", line)); },
+        };
+
+        match key {
+            "id" => { id = Some(value.to_string()); },
+            "goal" => {
+                goal = match Goal::from_name(value) {
+                    Some(goal) => Some(goal),
+                    None => { return Err(format!("Invalid goal in a puzzle: `{}`", value)); },
+                };
+            },
+            "player1" | "player2" => {
+                let pawn = match value.split_whitespace().collect::<Vec<&str>>()[..] {
+                    [square, walls] => match (Move::from_notation(square), walls.parse::<usize>()) {
+                        (Some(Move::Step(x, y)), Ok(walls)) if walls <= 10 => ((x, y), walls),
+                        _ => { return Err(format!("Invalid pawn in a puzzle: `{}`", value)); },
+                    },
+                    _ => { return Err(format!("Invalid pawn in a puzzle: `{}`", value)); },
+                };
+
+                if key == "player1" { player1 = Some(pawn); } else { player2 = Some(pawn); }
+            },
+            "walls" => {
+                for notation in value.split_whitespace() {
+                    match Move::from_notation(notation) {
+                        Some(Move::Step(_, _)) | None => { return Err(format!("Invalid wall in a puzzle: `{}`", notation)); },
+                        Some(wall) => { walls.push(wall); },
+                    }
+                }
+            },
+            "turn" => {
+                player1_turn = match value {
+                    "1" => true,
+                    "2" => false,
+                    _ => { return Err(format!("Invalid turn in a puzzle: `{}`", value)); },
+                };
+            },
+            "hint" => { hint = value.to_string(); },
+            "difficulty" => {
+                difficulty = match value.parse() {
+                    Ok(difficulty) => Some(difficulty),
+                    Err(_) => { return Err(format!("Invalid difficulty in a puzzle: `{}`", value)); },
+                };
+            },
+            _ => {},
+        }
+    }
+
+    let puzzle = match (id, goal, player1, player2) {
+        (Some(id), Some(goal), Some(player1), Some(player2)) => Puzzle { id, goal, player1, player2, walls, player1_turn, hint, difficulty },
+        _ => { return Err(format!("A puzzle needs `id`, `goal`, `player1` and `player2`: `{}`", lines[0])); },
+    };
+
+    puzzle.position()?;
+    Ok(puzzle)
+}
+
+pub fn serialize_puzzles(puzzles: &[Puzzle]) -> String {
+    let mut lines = vec![String::from("# Quoridor puzzles")];
+    let pawn = |(square, walls): ((i32, i32), usize)| format!("{} {}", Move::Step(square.0, square.1).to_notation(), walls);
+
+    for puzzle in puzzles.iter() {
+        lines.push(String::new());
+        lines.push(format!("id: {}", puzzle.id));
+        lines.push(format!("goal: {}", puzzle.goal.name()));
+        lines.push(format!("player1: {}", pawn(puzzle.player1)));
+        lines.push(format!("player2: {}", pawn(puzzle.player2)));
+
+        if !puzzle.walls.is_empty() {
+            lines.push(format!("walls: {}", puzzle.walls.iter().map(|wall| wall.to_notation()).collect::<Vec<String>>().join(" ")));
+        }
+
+        lines.push(format!("turn: {}", if puzzle.player1_turn { 1 } else { 2 }));
+
+        if !puzzle.hint.is_empty() {
+            lines.push(format!("hint: {}", puzzle.hint));
+        }
+
+        if let Some(difficulty) = puzzle.difficulty {
+            lines.push(format!("difficulty: {}", difficulty));
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+pub fn load_puzzles(path: &str) -> Result<Vec<Puzzle>, String> {
+    match read_string(path) {
+        Ok(s) => parse_puzzles(&s),
+        Err(_) => Err(format!("Failed to open file: {}", path)),
+    }
+}
+
+pub fn save_puzzles(puzzles: &[Puzzle], path: &str) -> Result<(), String> {
+    write_to_file(path, serialize_puzzles(puzzles).as_bytes()).map_err(|_| format!("Failed to write file: {}", path))
+}
+
+// the puzzles that come with the game
+pub fn bundled_puzzles() -> Result<Vec<Puzzle>, String> {
+    parse_puzzles(include_str!("puzzles.txt"))
+}
+
+// the bundled ones, then the generated ones in `PUZZLES_PATH` if there are any
+pub fn all_puzzles() -> Result<Vec<Puzzle>, String> {
+    let mut result = bundled_puzzles()?;
+
+    // it's okay to play without the generated puzzles
+    for puzzle in load_puzzles(PUZZLES_PATH).unwrap_or_default() {
+        if result.iter().all(|other| other.id != puzzle.id) {
+            result.push(puzzle);
+        }
+    }
+
+    Ok(result)
+}
+
+// A try at a puzzle. Every move of the player is checked with the endgame solver,
+// and the opponent answers with its best defence.
+#[derive(Clone)]
+pub struct Attempt {
+    pub position: Position,
+    goal: Goal,
+    player1: bool,  // the side of the player
+    replies: u8,  // the opponent's moves so far
+}
+
+pub enum Verdict {
+    Solved,
+    Failed(String),
+    Reply(Move),  // the opponent's answer, to be passed to `Attempt::reply`
+    Continue,  // the player moves again
+}
+
+enum Judgement {
+    Solved,
+    Failed(String),
+    Good(Move),  // the goal can still be reached, and this is the opponent's best answer
+    Unknown,  // the solver ran out of budget
+}
+
+impl Attempt {
+    pub fn new(puzzle: &Puzzle, start: Position) -> Self {
+        Attempt {
+            position: start,
+            goal: puzzle.goal,
+            player1: puzzle.player1_turn,
+            replies: 0,
+        }
+    }
+
+    pub fn is_players_turn(&self) -> bool {
+        self.position.player1_turn == self.player1 && self.position.winner().is_none()
+    }
+
+    // a legal move of the player
+    pub fn play(&mut self, mv: Move) -> Verdict {
+        let judgement = self.judge(mv);
+        self.position.apply(mv);
+
+        match judgement {
+            Judgement::Solved => Verdict::Solved,
+            Judgement::Failed(message) => Verdict::Failed(message),
+            Judgement::Good(reply) => Verdict::Reply(reply),
+            // the solver could not tell, so the player gets the benefit of the doubt
+            Judgement::Unknown => match best_reply(&self.position) {
+                Some(reply) => Verdict::Reply(reply),
+                None => Verdict::Solved,
+            },
+        }
+    }
+
+    pub fn reply(&mut self, mv: Move) -> Verdict {
+        self.position.apply(mv);
+        self.replies += 1;
+
+        if self.position.winner() == Some(!self.player1) {
+            Verdict::Failed(format!("Player {} made it", player_number(!self.player1)))
+        }
+
+        else if self.goal == Goal::Survive(self.replies) {
+            Verdict::Solved
+        }
+
+        else {
+            Verdict::Continue
+        }
+    }
+
+    fn judge(&self, mv: Move) -> Judgement {
+        let opponent = player_number(!self.player1);
+
+        if self.goal == Goal::Wall && matches!(mv, Move::Step(_, _)) {
+            return Judgement::Failed(String::from("The goal is to place a wall"));
+        }
+
+        let mut next = self.position.clone();
+        next.apply(mv);
+
+        if next.winner() == Some(self.player1) {
+            return Judgement::Solved;
+        }
+
+        // from the opponent's view
+        let solved = Solver::new(SOLVER_BUDGET).with_time_limit(SOLVER_TIME_LIMIT).solve(&next);
+
+        match (self.goal, solved) {
+            (_, None) => Judgement::Unknown,
+            (Goal::Race, Some((Outcome::Loss(_), Some(reply)))) => Judgement::Good(reply),
+            (Goal::Race, Some(_)) => Judgement::Failed(format!("Now player {} can win the race", opponent)),
+            (Goal::Wall, Some((Outcome::Loss(_), _))) => Judgement::Solved,
+            (Goal::Wall, Some(_)) => Judgement::Failed(String::from("That wall does not win the race")),
+            (Goal::Survive(moves), Some((outcome @ Outcome::Win(_), _))) if outcome.moves_to_end() <= moves - self.replies => {
+                Judgement::Failed(format!("Now player {} can win in {}", opponent, outcome.moves_to_end()))
+            },
+            (Goal::Survive(_), Some((_, Some(reply)))) => Judgement::Good(reply),
+            (Goal::Survive(_), Some((_, None))) => Judgement::Unknown,
+        }
+    }
+}
+
+// a short search, for the positions that the solver could not judge
+fn best_reply(position: &Position) -> Option<Move> {
+    let config = SearchConfig {
+        threads: 1,
+        max_depth: REPLY_DEPTH,
+        time_limit: Some(REPLY_TIME_LIMIT),
+        weights: Weights::load(PARAMS_PATH).unwrap_or_default(),
+    };
+
+    search(position, &config, &TranspositionTable::new(REPLY_TT_BITS), &AtomicBool::new(false)).best_move
+}
+
+fn player_number(player1: bool) -> usize {
+    if player1 { 1 } else { 2 }
+}
+
+pub const PUZZLES_PATH: &str = "puzzles.txt";
+
+// enough for a few hundred race tables; the puzzle screen judges the moves in a background thread
+const SOLVER_BUDGET: usize = 200_000;
+const SOLVER_TIME_LIMIT: Duration = Duration::from_millis(800);
+const REPLY_DEPTH: u8 = 4;
+const REPLY_TIME_LIMIT: Duration = Duration::from_millis(300);
+const REPLY_TT_BITS: u32 = 16;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_with_goal(goal: Goal) -> Puzzle {
+        bundled_puzzles().unwrap().into_iter().find(|puzzle| puzzle.goal == goal).unwrap()
+    }
+
+    #[test]
+    fn bundled_puzzles_parse() {
+        let puzzles = bundled_puzzles().unwrap();
+        assert!(!puzzles.is_empty());
+
+        for (index, puzzle) in puzzles.iter().enumerate() {
+            assert!(puzzle.position().is_ok(), "{}", puzzle.id);
+            assert!(puzzles[..index].iter().all(|other| other.id != puzzle.id), "{}", puzzle.id);
+        }
+    }
+
+    #[test]
+    fn puzzle_round_trip() {
+        let puzzle = bundled_puzzles().unwrap().remove(0);
+        let s = serialize_puzzles(std::slice::from_ref(&puzzle));
+        let parsed = parse_puzzles(&s).unwrap();
+
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].id, puzzle.id);
+        assert!(parsed[0].goal == puzzle.goal);
+        assert_eq!(parsed[0].player1, puzzle.player1);
+        assert_eq!(parsed[0].player2, puzzle.player2);
+        assert_eq!(parsed[0].walls, puzzle.walls);
+        assert_eq!(parsed[0].player1_turn, puzzle.player1_turn);
+        assert_eq!(parsed[0].hint, puzzle.hint);
+        assert_eq!(parsed[0].difficulty, puzzle.difficulty);
+        assert_eq!(serialize_puzzles(&parsed), s);
+    }
+
+    #[test]
+    fn invalid_puzzles_are_rejected() {
+        assert!(parse_puzzles("id: x\ngoal: fly\nplayer1: e1 0\nplayer2: e9 0\n").is_err());
+        assert!(parse_puzzles("id: x\ngoal: race\nplayer1: e1 0\n").is_err());
+    }
+
+    #[test]
+    fn solved_attempt() {
+        let puzzle = first_with_goal(Goal::Wall);
+        let solution = puzzle.solutions().unwrap()[0];
+        let mut attempt = Attempt::new(&puzzle, puzzle.position().unwrap());
+
+        assert!(attempt.is_players_turn());
+        assert!(matches!(attempt.play(solution), Verdict::Solved));
+    }
+
+    #[test]
+    fn failed_attempt() {
+        let puzzle = first_with_goal(Goal::Wall);
+        let start = puzzle.position().unwrap();
+        let step = start.legal_moves().into_iter().find(|mv| matches!(mv, Move::Step(_, _))).unwrap();
+
+        match Attempt::new(&puzzle, start).play(step) {
+            Verdict::Failed(message) => assert_eq!(message, "The goal is to place a wall"),
+            _ => panic!("a step is not a wall"),
+        }
+
+        let puzzle = first_with_goal(Goal::Race);
+        let start = puzzle.position().unwrap();
+        let solutions = puzzle.solutions().unwrap();
+        let wrong = start.legal_moves().into_iter().find(|mv| !solutions.contains(mv)).unwrap();
+
+        assert!(matches!(Attempt::new(&puzzle, start).play(wrong), Verdict::Failed(_)));
+    }
+}
@@ -0,0 +1,322 @@
+use super::{Attempt, Goal, Puzzle, Verdict};
+use crate::board::Board;
+use crate::engine::color::Color;
+use crate::engine::context::Context;
+use crate::engine::graphic::Graphic;
+use crate::engine::inputs::Inputs;
+use crate::engine::keys::{KEY_ESCAPE, KEY_LEFT, KEY_RIGHT};
+use crate::engine::sound::SoundAction;
+use crate::engine::transition;
+use crate::engine::widget::{button::Button, textbox::TextBox};
+use crate::game::Game;
+use crate::menu::MainMenu;
+use crate::popup::Popup;
+use crate::position::{Move, Position};
+use crate::screen::{self, ScreenScale};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+// The puzzle screen. It keeps the game of the main menu, and goes back to the menu with "Back" or ESC. A solved puzzle is saved to the profile that is picked on the left.
+pub struct PuzzleContext {
+    game: Box<Game>,
+    index: usize,  // in `Game::puzzles`
+    board: Board,
+    attempt: Attempt,
+    state: PuzzleState,
+    pending_reply: Option<(Move, Instant)>,  // the opponent's answer, played after `REPLY_DELAY`
+    judging: Option<JoinHandle<(Attempt, Verdict)>>,  // the solver checks the player's move in the background
+    feedback: String,
+    show_hint: bool,
+    profile: Option<usize>,  // in `Game::profiles`, `None` for a guest
+    curr_popup: Popup,
+    buttons: Vec<Button>,
+    screen_scale: Option<ScreenScale>,
+    frame_count: usize,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum PuzzleState {
+    Solving,
+    Solved,
+    Failed,
+}
+
+impl PuzzleContext {
+    // it starts with the first puzzle that the player has not solved yet
+    pub fn new(game: Box<Game>) -> Self {
+        let profile = game.last_profile();
+        let mut context = PuzzleContext {
+            game,
+            board: Board::new(Position::new()),
+            attempt: Attempt::new(&empty_puzzle(), Position::new()),
+            index: 0,
+            state: PuzzleState::Solving,
+            pending_reply: None,
+            judging: None,
+            feedback: String::new(),
+            show_hint: false,
+            profile,
+            curr_popup: Popup::dummy(),
+            buttons: vec![],
+            screen_scale: ScreenScale::calc(),
+            frame_count: 0,
+        };
+
+        context.index = (0..context.game.puzzles().len()).find(|index| !context.is_solved(*index)).unwrap_or(0);
+        context.label_buttons();
+        context.load(context.index);
+
+        context
+    }
+
+    fn load(&mut self, index: usize) {
+        let puzzle = match self.game.puzzles().get(index) {
+            Some(puzzle) => puzzle.clone(),
+            None => { return; },
+        };
+
+        // the bundled puzzles were checked at startup
+        let start = puzzle.position().unwrap_or_else(|_| Position::new());
+
+        self.index = index;
+        self.attempt = Attempt::new(&puzzle, start.clone());
+        self.board = Board::new(start);
+        self.state = PuzzleState::Solving;
+        self.pending_reply = None;
+        self.judging = None;  // the thread finishes on its own, and its verdict is for a puzzle that is gone
+        self.show_hint = false;
+        self.feedback = format!("You play player {}.", if puzzle.player1_turn { 1 } else { 2 });
+    }
+
+    // the label of the profile button changes, so the buttons are built again
+    fn label_buttons(&mut self) {
+        let profile = match self.profile {
+            Some(index) => self.game.profiles()[index].name.clone(),
+            None => String::from("guest"),
+        };
+
+        self.buttons = vec![
+            Button::new(0.0, 0.0, "Back"),
+            Button::new(0.0, 0.0, "Previous"),
+            Button::new(0.0, 0.0, "Next"),
+            Button::new(0.0, 0.0, "Retry"),
+            Button::new(0.0, 0.0, "Hint"),
+            Button::new(0.0, 0.0, &format!("Player: {}", profile)),
+        ];
+
+        self.locate_buttons();
+    }
+
+    fn locate_buttons(&mut self) {
+        let mut curr_y = 30.0;
+
+        for button in self.buttons.iter_mut() {
+            button.move_to(30.0, curr_y);
+            curr_y += 60.0;
+        }
+    }
+
+    fn is_solved(&self, index: usize) -> bool {
+        match (self.profile, self.game.puzzles().get(index)) {
+            (Some(profile), Some(puzzle)) => self.game.profiles()[profile].solved.contains(&puzzle.id),
+            _ => false,
+        }
+    }
+
+    fn handle_verdict(&mut self, verdict: Verdict) {
+        match verdict {
+            Verdict::Solved => {
+                self.state = PuzzleState::Solved;
+                self.feedback = String::from("Solved!");
+                self.curr_popup = Popup::new("Solved!");
+                self.save_progress();
+            },
+            Verdict::Failed(message) => {
+                self.state = PuzzleState::Failed;
+                self.feedback = format!("{}. Try again with \"Retry\".", message);
+                self.curr_popup = Popup::new("Not quite");
+            },
+            Verdict::Reply(mv) => {
+                self.feedback = String::from("Good move.");
+                self.pending_reply = Some((mv, Instant::now()));
+            },
+            Verdict::Continue => {
+                self.feedback = String::from("Your move.");
+            },
+        }
+
+        self.board.position = self.attempt.position.clone();
+    }
+
+    // the move is on the board at once, and the verdict comes when the solver is done
+    fn start_judging(&mut self, mv: Move) {
+        let mut attempt = self.attempt.clone();
+
+        self.board.position.apply(mv);
+        self.feedback = String::from("Checking the move...");
+        self.judging = Some(thread::spawn(move || {
+            let verdict = attempt.play(mv);
+            (attempt, verdict)
+        }));
+    }
+
+    fn update_judging(&mut self) {
+        match self.judging.take() {
+            Some(handle) if handle.is_finished() => match handle.join() {
+                Ok((attempt, verdict)) => {
+                    self.attempt = attempt;
+                    self.handle_verdict(verdict);
+                },
+                Err(_) => { self.load(self.index); },
+            },
+            judging => { self.judging = judging; },
+        }
+    }
+
+    fn save_progress(&mut self) {
+        let (profile, id) = match self.profile {
+            Some(profile) => (profile, self.game.puzzles()[self.index].id.clone()),
+            None => { return; },
+        };
+        let profile = &mut self.game.profiles_mut()[profile];
+
+        if profile.add_solved(&id) {
+            // it's okay to lose the progress
+            let _ = profile.save();
+        }
+    }
+
+    fn next_profile(&mut self) {
+        let count = self.game.profiles().len();
+
+        self.profile = match self.profile {
+            None if count > 0 => Some(0),
+            Some(index) if index + 1 < count => Some(index + 1),
+            _ => None,
+        };
+
+        self.label_buttons();
+    }
+
+    fn draw_info(&self, screen_w: f32, box_x: f32, box_y: f32) -> Vec<Graphic> {
+        let puzzles = self.game.puzzles();
+        let puzzle = match puzzles.get(self.index) {
+            Some(puzzle) => puzzle,
+            None => {
+                return TextBox::new("There are no puzzles.", box_x, box_y - 80.0, 666.0, 50.0, 32.0).set_color(Color::ui()).align_center().render();
+            },
+        };
+
+        let solved_count = (0..puzzles.len()).filter(|index| self.is_solved(*index)).count();
+        let progress = match self.profile {
+            Some(_) => format!("{} of {} solved", solved_count, puzzles.len()),
+            None => String::from("Pick a player to keep the progress."),
+        };
+        let difficulty = puzzle.difficulty.map(|difficulty| format!(", difficulty {}", difficulty)).unwrap_or_default();
+        let title = format!(
+            "Puzzle {}/{}: {}{}{}",
+            self.index + 1, puzzles.len(), puzzle.id, difficulty, if self.is_solved(self.index) { " (solved)" } else { "" },
+        );
+        let hint = if self.show_hint && !puzzle.hint.is_empty() { format!("Hint: {}", puzzle.hint) } else { String::new() };
+        let info_x = screen_w - 240.0;
+
+        [
+            TextBox::new(&title, box_x, box_y - 100.0, 666.0, 40.0, 32.0).set_color(Color::ui()).align_center().render(),
+            TextBox::new(&puzzle.goal.description(), box_x, box_y - 55.0, 666.0, 30.0, 24.0).set_color(Color::ui()).align_center().render(),
+            TextBox::new(&self.feedback, info_x, 30.0, 210.0, 150.0, 21.0).set_color(Color::ui()).render(),
+            TextBox::new(&hint, info_x, 200.0, 210.0, 150.0, 21.0).set_color(Color::ui()).render(),
+            TextBox::new(&progress, info_x, 370.0, 210.0, 60.0, 21.0).set_color(Color::ui()).render(),
+        ].concat()
+    }
+}
+
+impl Context for PuzzleContext {
+    fn frame(mut self: Box<Self>, inputs: Inputs) -> (Box<dyn Context>, Vec<Graphic>, Vec<SoundAction>) {
+        self.frame_count += 1;
+
+        if inputs.is_screen_size_changed || self.frame_count & 7 == 7 {
+            self.screen_scale = ScreenScale::calc();
+        }
+
+        let (screen_w, screen_h) = screen::screen_size(&self.screen_scale);
+        let mouse_pos = screen::scale_mouse(&self.screen_scale, inputs.mouse_pos);
+        let (box_x, box_y) = Board::origin(screen_w, screen_h);
+        let count = self.game.puzzles().len().max(1);
+        let mut clicked = None;
+
+        for (index, button) in self.buttons.iter_mut().enumerate() {
+            if button.check_mouse(mouse_pos) && inputs.mouse_pressed[0] {
+                clicked = Some(index);
+            }
+        }
+
+        match clicked {
+            Some(0) => {},
+            Some(1) => { self.load((self.index + count - 1) % count); },
+            Some(2) => { self.load((self.index + 1) % count); },
+            Some(3) => { self.load(self.index); },
+            Some(4) => { self.show_hint = true; },
+            Some(5) => { self.next_profile(); },
+            _ => {},
+        }
+
+        if inputs.key_pressed[KEY_LEFT] {
+            self.load((self.index + count - 1) % count);
+        }
+
+        if inputs.key_pressed[KEY_RIGHT] {
+            self.load((self.index + 1) % count);
+        }
+
+        self.update_judging();
+
+        let accepts_moves = self.state == PuzzleState::Solving && self.pending_reply.is_none() && self.judging.is_none() && self.attempt.is_players_turn();
+
+        match self.board.update(&inputs, mouse_pos, box_x, box_y, accepts_moves) {
+            Some(Ok(mv)) => { self.start_judging(mv); },
+            Some(Err(message)) => { self.curr_popup = Popup::new(&message); },
+            None => {},
+        }
+
+        if let Some((mv, since)) = self.pending_reply {
+            if since.elapsed() >= REPLY_DELAY {
+                self.pending_reply = None;
+
+                let verdict = self.attempt.reply(mv);
+                self.handle_verdict(verdict);
+            }
+        }
+
+        let mut graphics = [
+            self.board.render(box_x, box_y),
+            self.draw_info(screen_w, box_x, box_y),
+            self.buttons.iter_mut().flat_map(|button| button.render()).collect(),
+            self.curr_popup.render(),
+        ].concat();
+
+        graphics = screen::scale_screen(&self.screen_scale, graphics);
+
+        if clicked == Some(0) || inputs.key_pressed[KEY_ESCAPE] {
+            return transition::fade(Box::new(MainMenu::new(self.game)), graphics);
+        }
+
+        (self, graphics, vec![])
+    }
+}
+
+// before the first puzzle is loaded, and for an empty puzzle list
+fn empty_puzzle() -> Puzzle {
+    Puzzle {
+        id: String::new(),
+        goal: Goal::Race,
+        player1: ((0, 4), 0),
+        player2: ((8, 4), 0),
+        walls: vec![],
+        player1_turn: true,
+        hint: String::new(),
+        difficulty: None,
+    }
+}
+
+// so that the player sees their own move before the answer
+const REPLY_DELAY: Duration = Duration::from_millis(400);
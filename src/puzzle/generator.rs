@@ -0,0 +1,146 @@
+use super::{Goal, Puzzle};
+use crate::ai::endgame::{Outcome, Solver};
+use crate::ai::eval::{evaluate, Weights, MATE};
+use crate::position::{Move, Position};
+use crate::record::GameRecord;
+
+// Finds puzzles in finished games, usually self-play ones. Once the endgame solver can see through a position,
+// it looks for the ones where exactly one move wins, or exactly one move keeps the player from losing
+// within `SURVIVE_MOVES` moves. The difficulty is the depth that a plain search needs to find that move.
+pub fn find_puzzles(records: &[GameRecord], weights: &Weights) -> Vec<Puzzle> {
+    let mut result: Vec<Puzzle> = vec![];
+
+    for (index, record) in records.iter().enumerate() {
+        let positions = match record.replay() {
+            Ok(positions) => positions,
+            Err(_) => { continue; },
+        };
+        let mut walls: Vec<Move> = record.handicap.placed_wall().into_iter().collect();
+        let mut found = 0;
+
+        for (position, mv) in positions.iter().zip(record.moves.iter()) {
+            if found < MAX_PUZZLES_PER_GAME && position.walls.0 + position.walls.1 <= MAX_WALLS_IN_HAND {
+                if let Some(puzzle) = puzzle_at(position, &walls, weights) {
+                    if result.iter().all(|other| other.id != puzzle.id) {
+                        result.push(puzzle);
+                        found += 1;
+                    }
+                }
+            }
+
+            if !matches!(mv, Move::Step(_, _)) {
+                walls.push(*mv);
+            }
+        }
+
+        println!("puzzles {}/{}: {} found", index + 1, records.len(), found);
+    }
+
+    result
+}
+
+fn puzzle_at(position: &Position, walls: &[Move], weights: &Weights) -> Option<Puzzle> {
+    if position.legal_moves().len() < 2 {
+        return None;
+    }
+
+    let goal = match Solver::new(SOLVER_BUDGET).solve(position)?.0 {
+        Outcome::Win(plies) if plies >= MIN_WIN_PLIES => Goal::Race,
+        Outcome::Win(_) => { return None; },
+        Outcome::Loss(_) | Outcome::Draw => Goal::Survive(SURVIVE_MOVES),
+    };
+
+    let mut puzzle = Puzzle {
+        id: format!("gen-{:08x}", position.hash() as u32),
+        goal,
+        player1: (position.player1, position.walls.0),
+        player2: (position.player2, position.walls.1),
+        walls: walls.to_vec(),
+        player1_turn: position.player1_turn,
+        hint: String::new(),
+        difficulty: None,
+    };
+
+    let solution = match puzzle.solutions()?[..] {
+        [solution] => solution,
+        _ => { return None; },
+    };
+
+    if matches!(solution, Move::Step(_, _)) {
+        puzzle.hint = String::from("The answer is a pawn move");
+    }
+
+    else {
+        puzzle.hint = String::from("The answer is a wall");
+
+        // the only winning move is a wall, so the race puzzle is a wall puzzle
+        if goal == Goal::Race {
+            puzzle.goal = Goal::Wall;
+        }
+    }
+
+    puzzle.difficulty = Some(difficulty(position, solution, weights));
+    Some(puzzle)
+}
+
+// the shallowest depth at which `plain_best_move` plays the solution, `MAX_DIFFICULTY` if none below it does
+pub fn difficulty(position: &Position, solution: Move, weights: &Weights) -> u8 {
+    (1..MAX_DIFFICULTY).find(|depth| plain_best_move(position, *depth, weights) == Some(solution)).unwrap_or(MAX_DIFFICULTY)
+}
+
+// Alpha-beta with the evaluation at the leaves, and nothing else. The search of the cpu would see through
+// these positions at once with its race tables, so it can't tell an easy puzzle from a hard one.
+fn plain_best_move(position: &Position, depth: u8, weights: &Weights) -> Option<Move> {
+    let mut best_move = None;
+    let mut alpha = -MATE - 1;
+
+    for mv in position.legal_moves() {
+        let mut next = position.clone();
+        next.apply(mv);
+
+        let score = -plain_negamax(&next, depth - 1, -MATE - 1, -alpha, 1, weights);
+
+        if score > alpha {
+            alpha = score;
+            best_move = Some(mv);
+        }
+    }
+
+    best_move
+}
+
+fn plain_negamax(position: &Position, depth: u8, mut alpha: i32, beta: i32, ply: i32, weights: &Weights) -> i32 {
+    // only the player who just moved can have won
+    if position.winner().is_some() {
+        return -(MATE - ply);
+    }
+
+    if depth == 0 {
+        return evaluate(position, weights);
+    }
+
+    for mv in position.legal_moves() {
+        let mut next = position.clone();
+        next.apply(mv);
+
+        alpha = alpha.max(-plain_negamax(&next, depth - 1, -beta, -alpha, ply + 1, weights));
+
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    alpha
+}
+
+// the solver proves nothing with more walls than this
+const MAX_WALLS_IN_HAND: usize = 2;
+const SOLVER_BUDGET: usize = 50_000;
+
+// a win in a single move is not a puzzle
+const MIN_WIN_PLIES: u8 = 3;
+const SURVIVE_MOVES: u8 = 3;
+
+// the positions right before the end of a game are all alike
+const MAX_PUZZLES_PER_GAME: usize = 2;
+pub const MAX_DIFFICULTY: u8 = 5;
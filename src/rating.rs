@@ -0,0 +1,62 @@
+use crate::engine::file_io::{read_string, write_to_file};
+
+// The human's Elo rating against the adaptive cpu, saved in `RATING_PATH` as `name value` lines.
+#[derive(Clone, Copy)]
+pub struct Rating {
+    pub value: i32,
+    pub games: usize,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Rating {
+            value: INITIAL_RATING,
+            games: 0,
+        }
+    }
+}
+
+impl Rating {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let s = read_string(path).map_err(|_| format!("Failed to open file: {}", path))?;
+        let mut rating = Rating::default();
+
+        for line in s.lines() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            match line.split_once(char::is_whitespace).map(|(name, value)| (name, value.trim())) {
+                Some(("rating", value)) if value.parse::<i32>().is_ok() => { rating.value = value.parse().unwrap(); },
+                Some(("games", value)) if value.parse::<usize>().is_ok() => { rating.games = value.parse().unwrap(); },
+                _ => { return Err(format!("{}: invalid line in a rating file: `{}`", path, line)); },
+            }
+        }
+
+        Ok(rating)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let s = format!("# Quoridor rating\nrating {}\ngames {}\n", self.value, self.games);
+
+        write_to_file(path, s.as_bytes()).map_err(|_| format!("Failed to write file: {}", path))
+    }
+
+    // after a game against an opponent rated `opponent`: `score` is 1 for a win, 0.5 for a draw and 0 for a loss
+    pub fn update(&mut self, opponent: i32, score: f32) {
+        let expected = 1.0 / (1.0 + 10f32.powf((opponent - self.value) as f32 / 400.0));
+
+        // it moves fast while it knows little about the player
+        let k = if self.games < PROVISIONAL_GAMES { 64.0 } else { 24.0 };
+
+        self.value += (k * (score - expected)).round() as i32;
+        self.games += 1;
+    }
+}
+
+const INITIAL_RATING: i32 = 1000;
+const PROVISIONAL_GAMES: usize = 10;
+
+pub const RATING_PATH: &str = "rating.txt";
@@ -0,0 +1,387 @@
+use crate::ai::{personality::Personality, review::Annotation};
+use crate::engine::file_io::{read_string, write_to_file};
+use crate::handicap::{Handicap, MAX_HEAD_START};
+use crate::position::{Move, Position};
+
+// A finished (or abandoned) game, saved as a plain text file:
+//
+// # Quoridor game record
+// player1: human
+// player2: racer cpu
+// personality2: racer
+// result: 1
+// reason: resignation
+// walls: 10 6
+// head start: 1 0
+// wall for: 1
+// first: 2
+// time control: 300
+// move limit: 150
+// moves: b5 h5 c5 g5 d4v ...
+// times: 2.4 1.5 3.0 1.5 8.2 ...
+// annotations: - - ?! - ?? ...
+// scores: 50 -50 40 -120 300 ...
+//
+// `annotations` and `scores` come from the post-game review: a symbol for each move (`-` for the best moves),
+// and player 1's evaluation before each move and after the last one.
+// `result` is `1`, `2`, `=` (a draw) or `*` (not finished), and `reason` tells how the game ended.
+// `walls`, `head start`, `wall for` and `first` are the handicap, and only written when they're not the normal start.
+// `time control` is the seconds that each player has for the whole game, and `times` the seconds spent on each move.
+// Every key but `moves` is optional.
+// Unknown keys are ignored, so that old readers can open newer records.
+// how a game ended: the winner reached the goal or the loser resigned, or the players drew
+#[derive(Copy, Clone, PartialEq)]
+pub enum EndReason {
+    Goal,
+    Resignation,
+    Agreement,
+    Repetition,
+    MoveLimit,
+    Time,  // the loser ran out of time
+}
+
+impl EndReason {
+    pub const ALL: [EndReason; 6] = [
+        EndReason::Goal, EndReason::Resignation, EndReason::Agreement, EndReason::Repetition, EndReason::MoveLimit, EndReason::Time,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EndReason::Goal => "goal",
+            EndReason::Resignation => "resignation",
+            EndReason::Agreement => "agreement",
+            EndReason::Repetition => "repetition",
+            EndReason::MoveLimit => "move limit",
+            EndReason::Time => "time",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        EndReason::ALL.into_iter().find(|reason| reason.name() == name)
+    }
+}
+
+#[derive(Clone)]
+pub struct GameRecord {
+    pub moves: Vec<Move>,
+    pub winner: Option<bool>,  // `Some(true)` if player 1 won
+    pub reason: Option<EndReason>,  // `None` if the game is not finished
+    pub handicap: Handicap,
+    pub players: (String, String),  // names of the seats, empty if unknown
+    pub personalities: (Option<Personality>, Option<Personality>),  // of the cpu seats
+    pub time_control: Option<usize>,  // seconds for each player
+    pub move_limit: Option<usize>,
+    pub times: Vec<f32>,  // seconds spent on each move, empty if unknown
+    pub annotations: Vec<Annotation>,  // empty if the game is not reviewed
+    pub scores: Vec<i32>,
+}
+
+impl GameRecord {
+    pub fn new() -> Self {
+        GameRecord {
+            moves: vec![],
+            winner: None,
+            reason: None,
+            handicap: Handicap::default(),
+            players: (String::new(), String::new()),
+            personalities: (None, None),
+            time_control: None,
+            move_limit: None,
+            times: vec![],
+            annotations: vec![],
+            scores: vec![],
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut record = GameRecord::new();
+
+        for line in s.lines() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => { return Err(format!("Invalid line in a game record: `{}`", line)); },
+            };
+
+            match key {
+                "result" => {
+                    record.winner = match value {
+                        "1" => Some(true),
+                        "2" => Some(false),
+                        _ => None,
+                    };
+                },
+                "reason" => {
+                    record.reason = match EndReason::from_name(value) {
+                        Some(reason) => Some(reason),
+                        None => { return Err(format!("Invalid reason in a game record: `{}`", value)); },
+                    };
+                },
+                "walls" => {
+                    record.handicap.walls = match parse_pair(value) {
+                        Some((walls1, walls2)) => (walls1 as usize, walls2 as usize),
+                        None => { return Err(format!("Invalid walls in a game record: `{}`", value)); },
+                    };
+                },
+                "head start" => {
+                    record.handicap.head_start = match parse_pair(value) {
+                        Some(head_start) if head_start.0 <= MAX_HEAD_START && head_start.1 <= MAX_HEAD_START => head_start,
+                        _ => { return Err(format!("Invalid head start in a game record: `{}`", value)); },
+                    };
+                },
+                "wall for" => {
+                    record.handicap.wall_for = match value {
+                        "1" => Some(true),
+                        "2" => Some(false),
+                        _ => { return Err(format!("Invalid player in a game record: `{}`", value)); },
+                    };
+                },
+                "first" => { record.handicap.player1_first = value != "2"; },
+                "player1" => { record.players.0 = value.to_string(); },
+                "player2" => { record.players.1 = value.to_string(); },
+                "personality1" => { record.personalities.0 = Personality::from_name(value); },
+                "personality2" => { record.personalities.1 = Personality::from_name(value); },
+                "time control" => {
+                    record.time_control = match value.parse() {
+                        Ok(seconds) => Some(seconds),
+                        Err(_) => { return Err(format!("Invalid time control in a game record: `{}`", value)); },
+                    };
+                },
+                "move limit" => {
+                    record.move_limit = match value.parse() {
+                        Ok(limit) => Some(limit),
+                        Err(_) => { return Err(format!("Invalid move limit in a game record: `{}`", value)); },
+                    };
+                },
+                "times" => {
+                    for time in value.split_whitespace() {
+                        match time.parse() {
+                            Ok(time) => { record.times.push(time); },
+                            Err(_) => { return Err(format!("Invalid time in a game record: `{}`", time)); },
+                        }
+                    }
+                },
+                "annotations" => {
+                    for symbol in value.split_whitespace() {
+                        match Annotation::from_symbol(if symbol == "-" { "" } else { symbol }) {
+                            Some(annotation) => { record.annotations.push(annotation); },
+                            None => { return Err(format!("Invalid annotation in a game record: `{}`", symbol)); },
+                        }
+                    }
+                },
+                "scores" => {
+                    for score in value.split_whitespace() {
+                        match score.parse() {
+                            Ok(score) => { record.scores.push(score); },
+                            Err(_) => { return Err(format!("Invalid score in a game record: `{}`", score)); },
+                        }
+                    }
+                },
+                "moves" => {
+                    for notation in value.split_whitespace() {
+                        match Move::from_notation(notation) {
+                            Some(mv) => { record.moves.push(mv); },
+                            None => { return Err(format!("Invalid move in a game record: `{}`", notation)); },
+                        }
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        Ok(record)
+    }
+
+    pub fn serialize(&self) -> String {
+        let result = match (self.winner, self.reason) {
+            (Some(true), _) => "1",
+            (Some(false), _) => "2",
+            (None, Some(_)) => "=",
+            (None, None) => "*",
+        };
+        let moves: Vec<String> = self.moves.iter().map(|mv| mv.to_notation()).collect();
+        let mut lines = vec![String::from("# Quoridor game record")];
+
+        for (index, name) in [&self.players.0, &self.players.1].into_iter().enumerate() {
+            if !name.is_empty() {
+                lines.push(format!("player{}: {}", index + 1, name));
+            }
+        }
+
+        for (index, personality) in [self.personalities.0, self.personalities.1].into_iter().enumerate() {
+            if let Some(personality) = personality {
+                lines.push(format!("personality{}: {}", index + 1, personality.name()));
+            }
+        }
+
+        lines.push(format!("result: {}", result));
+
+        if let Some(reason) = self.reason {
+            lines.push(format!("reason: {}", reason.name()));
+        }
+
+        let handicap = &self.handicap;
+        let normal = Handicap::default();
+
+        if handicap.walls != normal.walls {
+            lines.push(format!("walls: {} {}", handicap.walls.0, handicap.walls.1));
+        }
+
+        if handicap.head_start != normal.head_start {
+            lines.push(format!("head start: {} {}", handicap.head_start.0, handicap.head_start.1));
+        }
+
+        if let Some(player1) = handicap.wall_for {
+            lines.push(format!("wall for: {}", if player1 { 1 } else { 2 }));
+        }
+
+        if !handicap.player1_first {
+            lines.push(String::from("first: 2"));
+        }
+
+        if let Some(seconds) = self.time_control {
+            lines.push(format!("time control: {}", seconds));
+        }
+
+        if let Some(limit) = self.move_limit {
+            lines.push(format!("move limit: {}", limit));
+        }
+
+        lines.push(format!("moves: {}", moves.join(" ")));
+
+        if !self.times.is_empty() {
+            let times: Vec<String> = self.times.iter().map(|time| format!("{:.1}", time)).collect();
+            lines.push(format!("times: {}", times.join(" ")));
+        }
+
+        if !self.annotations.is_empty() {
+            let symbols: Vec<&str> = self.annotations.iter().map(|annotation| if *annotation == Annotation::Best { "-" } else { annotation.symbol() }).collect();
+            lines.push(format!("annotations: {}", symbols.join(" ")));
+        }
+
+        if !self.scores.is_empty() {
+            let scores: Vec<String> = self.scores.iter().map(|score| score.to_string()).collect();
+            lines.push(format!("scores: {}", scores.join(" ")));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        match read_string(path) {
+            Ok(s) => GameRecord::parse(&s).map_err(|e| format!("{}: {}", path, e)),
+            Err(_) => Err(format!("Failed to open file: {}", path)),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        write_to_file(path, self.serialize().as_bytes()).map_err(|_| format!("Failed to write file: {}", path))
+    }
+
+    // the position before each move, checking that every move is legal
+    pub fn replay(&self) -> Result<Vec<Position>, String> {
+        let mut result = Vec::with_capacity(self.moves.len());
+        let mut position = self.handicap.position();
+
+        for (ply, mv) in self.moves.iter().enumerate() {
+            if !position.is_legal(*mv) {
+                return Err(format!("Illegal move at ply {}: `{}`", ply + 1, mv.to_notation()));
+            }
+
+            result.push(position.clone());
+            position.apply(*mv);
+        }
+
+        Ok(result)
+    }
+}
+
+// `a b`, like `walls: 10 6`
+fn parse_pair(s: &str) -> Option<(i32, i32)> {
+    let numbers: Vec<i32> = s.split_whitespace().map(|n| n.parse().ok()).collect::<Option<Vec<i32>>>()?;
+
+    match numbers[..] {
+        [a, b] if a >= 0 && b >= 0 => Some((a, b)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moves(notations: &str) -> Vec<Move> {
+        notations.split_whitespace().map(|notation| Move::from_notation(notation).unwrap()).collect()
+    }
+
+    #[test]
+    fn record_round_trip() {
+        let mut record = GameRecord::new();
+        record.moves = moves("b5 h5 c5 g5 d4v");
+        record.winner = Some(false);
+        record.reason = Some(EndReason::Time);
+        record.handicap = Handicap {
+            walls: (10, 6),
+            head_start: (1, 0),
+            wall_for: Some(true),
+            player1_first: false,
+        };
+        record.players = (String::from("human"), String::from("racer cpu"));
+        record.personalities = (None, Some(Personality::Racer));
+        record.time_control = Some(300);
+        record.move_limit = Some(150);
+        record.times = vec![2.4, 1.5, 3.0, 1.5, 8.2];
+        record.annotations = vec![Annotation::Best, Annotation::Best, Annotation::Inaccuracy, Annotation::Best, Annotation::Blunder];
+        record.scores = vec![50, -50, 40, -120, 300, -310];
+
+        let s = record.serialize();
+        let parsed = GameRecord::parse(&s).unwrap();
+
+        assert_eq!(parsed.moves, record.moves);
+        assert_eq!(parsed.winner, record.winner);
+        assert!(parsed.reason == record.reason);
+        assert!(parsed.handicap == record.handicap);
+        assert_eq!(parsed.players, record.players);
+        assert_eq!(parsed.personalities, record.personalities);
+        assert_eq!(parsed.time_control, record.time_control);
+        assert_eq!(parsed.move_limit, record.move_limit);
+        assert_eq!(parsed.times, record.times);
+        assert_eq!(parsed.annotations, record.annotations);
+        assert_eq!(parsed.scores, record.scores);
+        assert_eq!(parsed.serialize(), s);
+    }
+
+    #[test]
+    fn minimal_record() {
+        let parsed = GameRecord::parse("moves: e2 e8\nsomething new: 1\n").unwrap();
+
+        assert_eq!(parsed.moves, moves("e2 e8"));
+        assert_eq!(parsed.winner, None);
+        assert!(parsed.reason.is_none());
+        assert!(parsed.handicap == Handicap::default());
+        assert!(parsed.times.is_empty());
+    }
+
+    #[test]
+    fn invalid_records_are_rejected() {
+        assert!(GameRecord::parse("no colon here\n").is_err());
+        assert!(GameRecord::parse("reason: boredom\nmoves: e2\n").is_err());
+        assert!(GameRecord::parse("walls: 10\nmoves: e2\n").is_err());
+        assert!(GameRecord::parse("moves: e2 z9\n").is_err());
+    }
+
+    #[test]
+    fn replay_rejects_illegal_moves() {
+        let mut record = GameRecord::new();
+        record.moves = moves("b5 h5 c5");
+        assert_eq!(record.replay().unwrap().len(), 3);
+
+        record.moves = moves("b5 d5");
+        assert!(record.replay().is_err());
+    }
+}
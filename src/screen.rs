@@ -0,0 +1,69 @@
+use crate::engine::global::GLOBAL_ENV;
+use crate::engine::graphic::Graphic;
+
+// Every screen is laid out for a 1536x800 window. A smaller window gets the same layout, zoomed out
+// and centered, and the mouse is mapped back to the layout's coordinates.
+pub struct ScreenScale {
+    offset_x: f32,
+    offset_y: f32,
+    zoom: f32,
+}
+
+impl ScreenScale {
+    // `None` if the window is big enough to draw everything as it is
+    pub fn calc() -> Option<Self> {
+        let (screen_w, screen_h) = unsafe {GLOBAL_ENV.screen_size};
+
+        if screen_w >= 760.0 && screen_h >= 1160.0 {
+            None
+        }
+
+        else {
+            let vert_zoom = screen_h / 800.0;
+            let horiz_zoom = screen_w / 1536.0;
+            let zoom = vert_zoom.min(horiz_zoom);
+
+            let (offset_x, offset_y) = if vert_zoom > horiz_zoom {
+                (0.0, (screen_h - 800.0 * zoom) / 2.0)
+            }
+
+            else {
+                ((screen_w - 1536.0 * zoom) / 2.0, 0.0)
+            };
+
+            Some(ScreenScale {
+                offset_x, offset_y, zoom
+            })
+        }
+    }
+}
+
+pub fn scale_mouse(scale: &Option<ScreenScale>, mouse_pos: (f32, f32)) -> (f32, f32) {
+    match scale {
+        None => mouse_pos,
+        Some(s) => ((mouse_pos.0 - s.offset_x) / s.zoom, (mouse_pos.1 - s.offset_y) / s.zoom),
+    }
+}
+
+pub fn scale_screen(scale: &Option<ScreenScale>, graphics: Vec<Graphic>) -> Vec<Graphic> {
+    match scale {
+        None => graphics,
+        Some(s) => {
+            graphics.iter().map(
+                |graphic|
+                graphic.scale(0.0, 0.0, s.zoom, s.zoom).move_rel(s.offset_x, s.offset_y)
+            ).collect()
+        },
+    }
+}
+
+// the size of the layout
+pub fn screen_size(scale: &Option<ScreenScale>) -> (f32, f32) {
+    if scale.is_none() {
+        unsafe { GLOBAL_ENV.screen_size }
+    }
+
+    else {
+        (1536.0, 800.0)
+    }
+}
@@ -0,0 +1,308 @@
+use crate::engine::color::Color;
+use crate::engine::context::{Context, Cover};
+use crate::engine::file_io::{read_string, write_to_file};
+use crate::engine::global::{global_env, GLOBAL_ENV};
+use crate::engine::graphic::Graphic;
+use crate::engine::inputs::Inputs;
+use crate::engine::keys::KEY_ESCAPE;
+use crate::engine::sound::SoundAction;
+use crate::engine::widget::{button::Button, textbox::TextBox};
+use crate::screen::{self, ScreenScale};
+
+// The options that outlive a game, saved as `SETTINGS_PATH`:
+//
+// # Quoridor settings
+// paths: on
+// heatmap: off
+// explanations: on
+// transitions: on
+//
+// The hotkeys of a game change the overlays of that game only.
+#[derive(Clone, PartialEq)]
+pub struct Settings {
+    pub show_paths: bool,
+    pub show_heatmap: bool,
+    pub show_explanation: bool,
+    pub transitions: bool,  // the fades between the screens
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            show_paths: false,
+            show_heatmap: false,
+            show_explanation: true,
+            transitions: true,
+        }
+    }
+}
+
+impl Settings {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut settings = Settings::default();
+
+        for line in s.lines() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => { return Err(format!("Invalid line in the settings: `{}`", line)); },
+            };
+            let on = match value {
+                "on" => true,
+                "off" => false,
+                _ => { return Err(format!("Invalid value in the settings: `{}`", value)); },
+            };
+
+            match key {
+                "paths" => { settings.show_paths = on; },
+                "heatmap" => { settings.show_heatmap = on; },
+                "explanations" => { settings.show_explanation = on; },
+                "transitions" => { settings.transitions = on; },
+                _ => {},
+            }
+        }
+
+        Ok(settings)
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut lines = vec![String::from("# Quoridor settings")];
+
+        for (key, on) in self.entries() {
+            lines.push(format!("{}: {}", key, on_off(on)));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        match read_string(path) {
+            Ok(s) => Settings::parse(&s).map_err(|e| format!("{}: {}", path, e)),
+            Err(_) => Err(format!("Failed to open file: {}", path)),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        write_to_file(path, self.serialize().as_bytes()).map_err(|_| format!("Failed to write file: {}", path))
+    }
+
+    // the settings that belong to the engine, not to a game
+    pub fn apply(&self) {
+        unsafe { GLOBAL_ENV.fade_frames = if self.transitions { FADE_FRAMES } else { 0 }; }
+    }
+
+    fn entries(&self) -> [(&'static str, bool); 4] {
+        [
+            ("paths", self.show_paths),
+            ("heatmap", self.show_heatmap),
+            ("explanations", self.show_explanation),
+            ("transitions", self.transitions),
+        ]
+    }
+
+    fn toggle(&mut self, index: usize) {
+        match index {
+            0 => { self.show_paths = !self.show_paths; },
+            1 => { self.show_heatmap = !self.show_heatmap; },
+            2 => { self.show_explanation = !self.show_explanation; },
+            _ => { self.transitions = !self.transitions; },
+        }
+    }
+}
+
+// A panel of toggles, one for each setting, and "Done". Every click saves the settings at once.
+pub struct SettingsPanel {
+    pub settings: Settings,
+    buttons: Vec<Button>,  // the toggles in the order of `Settings::entries`, then "Done"
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum PanelEvent {
+    Changed,
+    Closed,
+}
+
+impl SettingsPanel {
+    pub fn new(settings: Settings) -> Self {
+        let mut panel = SettingsPanel {
+            settings,
+            buttons: vec![],
+        };
+
+        panel.label_buttons();
+        panel
+    }
+
+    fn label_buttons(&mut self) {
+        self.buttons = self.settings.entries().iter().map(
+            |(key, on)| Button::new(0.0, 0.0, &format!("{}: {}", capitalize(key), on_off(*on)))
+        ).collect();
+        self.buttons.push(Button::new(0.0, 0.0, "Done"));
+    }
+
+    // the panel is centered at (center_x, center_y)
+    pub fn update(&mut self, inputs: &Inputs, mouse_pos: (f32, f32), center_x: f32, center_y: f32) -> Option<PanelEvent> {
+        let (panel_x, panel_y, _, _) = panel_rect(center_x, center_y);
+        let mut clicked = None;
+
+        for (index, button) in self.buttons.iter_mut().enumerate() {
+            button.move_to(panel_x + (PANEL_W - 180.0) / 2.0, panel_y + ROW_Y + index as f32 * ROW_GAP);
+
+            if button.check_mouse(mouse_pos) && inputs.mouse_pressed[0] {
+                clicked = Some(index);
+            }
+        }
+
+        match clicked {
+            Some(index) if index + 1 == self.buttons.len() => Some(PanelEvent::Closed),
+            Some(index) => {
+                self.settings.toggle(index);
+                self.settings.apply();
+                self.label_buttons();
+
+                // it's okay to lose the settings
+                let _ = self.settings.save(SETTINGS_PATH);
+
+                Some(PanelEvent::Changed)
+            },
+            None => None,
+        }
+    }
+
+    pub fn render(&mut self, center_x: f32, center_y: f32) -> Vec<Graphic> {
+        let (panel_x, panel_y, panel_w, panel_h) = panel_rect(center_x, center_y);
+
+        [
+            vec![Graphic::new_round_rect(panel_x, panel_y, panel_w, panel_h, 16.0, 0.0, Color::new(16, 16, 16, 224))],
+            TextBox::new("Settings", panel_x, panel_y + 20.0, panel_w, 50.0, 32.0).set_color(Color::ui()).align_center().render(),
+            self.buttons.iter_mut().flat_map(|button| button.render()).collect(),
+        ].concat()
+    }
+}
+
+// The settings panel over another screen, which waits under it. "Done" or ESC pops it with the `Settings`,
+// for the screen under it to apply.
+pub struct SettingsOverlay {
+    panel: SettingsPanel,
+    is_closed: bool,
+    screen_scale: Option<ScreenScale>,
+    frame_count: usize,
+}
+
+impl SettingsOverlay {
+    pub fn new(settings: Settings) -> Self {
+        SettingsOverlay {
+            panel: SettingsPanel::new(settings),
+            is_closed: false,
+            screen_scale: ScreenScale::calc(),
+            frame_count: 0,
+        }
+    }
+}
+
+impl Context for SettingsOverlay {
+    fn frame(mut self: Box<Self>, inputs: Inputs) -> (Box<dyn Context>, Vec<Graphic>, Vec<SoundAction>) {
+        self.frame_count += 1;
+
+        if inputs.is_screen_size_changed || self.frame_count & 7 == 7 {
+            self.screen_scale = ScreenScale::calc();
+        }
+
+        let (screen_w, screen_h) = screen::screen_size(&self.screen_scale);
+        let mouse_pos = screen::scale_mouse(&self.screen_scale, inputs.mouse_pos);
+        let (center_x, center_y) = (screen_w / 2.0, screen_h / 2.0);
+        let event = self.panel.update(&inputs, mouse_pos, center_x, center_y);
+
+        if (event == Some(PanelEvent::Closed) || inputs.key_pressed[KEY_ESCAPE]) && !self.is_closed {
+            self.is_closed = true;
+            global_env().pop_context(Some(Box::new(self.panel.settings.clone())));
+        }
+
+        let graphics = [
+            vec![Graphic::new_rect(0.0, 0.0, screen_w, screen_h, 0.0, Color::new(0, 0, 0, 96))],
+            self.panel.render(center_x, center_y),
+        ].concat();
+
+        let graphics = screen::scale_screen(&self.screen_scale, graphics);
+
+        (self, graphics, vec![])
+    }
+
+    fn cover(&self) -> Cover {
+        Cover::Overlay { block_input: true, block_updates: true }
+    }
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on { "on" } else { "off" }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn panel_rect(center_x: f32, center_y: f32) -> (f32, f32, f32, f32) {
+    (center_x - PANEL_W / 2.0, center_y - PANEL_H / 2.0, PANEL_W, PANEL_H)
+}
+
+pub const SETTINGS_PATH: &str = "settings.txt";
+const FADE_FRAMES: usize = 10;
+
+const PANEL_W: f32 = 300.0;
+const PANEL_H: f32 = 380.0;
+const ROW_Y: f32 = 90.0;
+const ROW_GAP: f32 = 50.0;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_round_trip() {
+        let settings = Settings {
+            show_paths: true,
+            show_heatmap: false,
+            show_explanation: false,
+            transitions: true,
+        };
+
+        assert!(Settings::parse(&settings.serialize()).unwrap() == settings);
+        assert!(Settings::parse(&Settings::default().serialize()).unwrap() == Settings::default());
+    }
+
+    #[test]
+    fn missing_and_unknown_keys() {
+        let settings = Settings::parse("# comment\n\nheatmap: on\nvolume: on\n").unwrap();
+
+        assert!(settings.show_heatmap);
+        assert_eq!(settings.show_explanation, Settings::default().show_explanation);
+        assert_eq!(settings.transitions, Settings::default().transitions);
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected() {
+        assert!(Settings::parse("paths on\n").is_err());
+        assert!(Settings::parse("paths: maybe\n").is_err());
+    }
+
+    #[test]
+    fn toggle_follows_entries() {
+        let mut settings = Settings::default();
+
+        for index in 0..settings.entries().len() {
+            let before = settings.entries()[index].1;
+            settings.toggle(index);
+            assert_eq!(settings.entries()[index].1, !before);
+        }
+    }
+}
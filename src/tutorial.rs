@@ -0,0 +1,259 @@
+use crate::board::{self, Board, TRAP_MESSAGE};
+use crate::engine::color::Color;
+use crate::engine::context::Context;
+use crate::engine::graphic::Graphic;
+use crate::engine::inputs::Inputs;
+use crate::engine::keys::{KEY_ENTER, KEY_ESCAPE};
+use crate::engine::sound::SoundAction;
+use crate::engine::transition;
+use crate::engine::widget::{button::Button, textbox::TextBox};
+use crate::game::Game;
+use crate::menu::MainMenu;
+use crate::popup::Popup;
+use crate::position::{Move, Position};
+use crate::screen::{self, ScreenScale};
+
+// The rules, one at a time, on a live board. The player plays player 1 and the other pawn stays put.
+// Every step points at squares or gaps on the board, and "Next" shows up only once the player
+// has done what the step asks. A move that is legal but not the one asked for starts the step over.
+pub struct Tutorial {
+    game: Box<Game>,  // of the main menu, to go back to
+    step: usize,  // in `STEPS`, `STEPS.len()` for the last page
+    board: Board,
+    done: bool,
+    curr_popup: Popup,
+    buttons: Vec<Button>,  // back, next
+    screen_scale: Option<ScreenScale>,
+    frame_count: usize,
+}
+
+struct Step {
+    title: &'static str,
+    text: &'static str,
+    player1: (i32, i32),
+    player2: (i32, i32),
+    walls: &'static [Move],  // on the board
+    walls_in_hand: usize,
+    highlights: &'static [Move],
+    action: Action,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Action {
+    AnyStep,
+    StepTo(i32, i32),
+    AnyWall,
+    TrappingWall,  // try a wall that the rules forbid
+    Goal,
+}
+
+impl Tutorial {
+    pub fn new(game: Box<Game>) -> Self {
+        let mut tutorial = Tutorial {
+            game,
+            step: 0,
+            board: Board::new(Position::new()),
+            done: false,
+            curr_popup: Popup::dummy(),
+            buttons: vec![Button::new(30.0, 30.0, "Back"), Button::new(30.0, 90.0, "Next")],
+            screen_scale: ScreenScale::calc(),
+            frame_count: 0,
+        };
+
+        tutorial.load(0);
+        tutorial
+    }
+
+    fn load(&mut self, step: usize) {
+        self.step = step;
+        self.done = false;
+
+        let step = match STEPS.get(step) {
+            Some(step) => step,
+            None => { return; },
+        };
+
+        let mut position = Position::new();
+        position.player1 = step.player1;
+        position.player2 = step.player2;
+
+        for wall in step.walls.iter() {
+            position.apply(*wall);
+        }
+
+        position.walls = (step.walls_in_hand, 0);
+        position.player1_turn = true;
+        position.update_hash();
+
+        self.board = Board::new(position);
+    }
+
+    // `Ok(mv)` from the board is a legal move, and `Err` is the message of an illegal one
+    fn try_action(&mut self, result: Result<Move, String>) {
+        let step = &STEPS[self.step];
+
+        let is_done = match (step.action, &result) {
+            (Action::AnyStep, Ok(Move::Step(_, _))) => true,
+            (Action::StepTo(x, y), Ok(Move::Step(to_x, to_y))) => (x, y) == (*to_x, *to_y),
+            (Action::AnyWall, Ok(Move::Vertical(_, _) | Move::Horizontal(_, _))) => true,
+            (Action::TrappingWall, Err(message)) => message == TRAP_MESSAGE,
+            (Action::Goal, Ok(Move::Step(x, _))) => *x == 8,
+            _ => false,
+        };
+
+        match result {
+            Ok(mv) if is_done => {
+                self.board.position.apply(mv);
+
+                // the other pawn waits
+                self.board.position.player1_turn = true;
+                self.board.position.update_hash();
+
+                self.curr_popup = Popup::new("Well done!");
+            },
+            Ok(_) => {
+                self.curr_popup = Popup::new("Not quite, try again");
+                self.load(self.step);
+            },
+            Err(message) => {
+                self.curr_popup = Popup::new(&message);
+            },
+        }
+
+        self.done = is_done;
+    }
+
+    fn draw_step(&self, screen_w: f32, box_x: f32, box_y: f32) -> Vec<Graphic> {
+        let (title, text, status) = match STEPS.get(self.step) {
+            Some(step) => (
+                format!("{}/{}: {}", self.step + 1, STEPS.len(), step.title),
+                step.text,
+                if self.done { "Click \"Next\" to go on." } else { "" },
+            ),
+            None => (String::from("That's all!"), LAST_PAGE, ""),
+        };
+        let text_x = screen_w - 270.0;
+
+        [
+            TextBox::new(&title, box_x, box_y - 100.0, 666.0, 40.0, 32.0).set_color(Color::ui()).align_center().render(),
+            TextBox::new(text, text_x, 30.0, 240.0, 400.0, 21.0).set_color(Color::ui()).render(),
+            TextBox::new(status, text_x, 450.0, 240.0, 60.0, 21.0).set_color(Color::ui()).render(),
+        ].concat()
+    }
+}
+
+impl Context for Tutorial {
+    fn frame(mut self: Box<Self>, inputs: Inputs) -> (Box<dyn Context>, Vec<Graphic>, Vec<SoundAction>) {
+        self.frame_count += 1;
+
+        if inputs.is_screen_size_changed || self.frame_count & 7 == 7 {
+            self.screen_scale = ScreenScale::calc();
+        }
+
+        let (screen_w, screen_h) = screen::screen_size(&self.screen_scale);
+        let mouse_pos = screen::scale_mouse(&self.screen_scale, inputs.mouse_pos);
+        let (box_x, box_y) = Board::origin(screen_w, screen_h);
+        let is_over = self.step >= STEPS.len();
+
+        let is_back = self.buttons[0].check_mouse(mouse_pos) && inputs.mouse_pressed[0] || inputs.key_pressed[KEY_ESCAPE];
+        let is_next = self.done && (self.buttons[1].check_mouse(mouse_pos) && inputs.mouse_pressed[0] || inputs.key_pressed[KEY_ENTER]);
+
+        if is_next {
+            self.load(self.step + 1);
+        }
+
+        else if let Some(result) = self.board.update(&inputs, mouse_pos, box_x, box_y, !self.done && !is_over) {
+            self.try_action(result);
+        }
+
+        let highlights = match STEPS.get(self.step) {
+            Some(step) if !self.done => step.highlights,
+            _ => &[],
+        };
+
+        let mut graphics = [
+            if is_over { vec![] } else { self.board.render(box_x, box_y) },
+            board::draw_highlights(highlights, box_x, box_y, self.frame_count),
+            self.draw_step(screen_w, box_x, box_y),
+            self.buttons[0].render(),
+            if self.done { self.buttons[1].render() } else { vec![] },
+            self.curr_popup.render(),
+        ].concat();
+
+        graphics = screen::scale_screen(&self.screen_scale, graphics);
+
+        if is_back {
+            return transition::fade(Box::new(MainMenu::new(self.game)), graphics);
+        }
+
+        (self, graphics, vec![])
+    }
+}
+
+const STEPS: [Step; 6] = [
+    Step {
+        title: "Moving",
+        text: "You are the green pawn, and your goal is the right edge of the board. The purple pawn wants to reach the left edge.\n\nOn your turn, you move your pawn one square up, down, left or right. The dots show where it can go.\n\nMove your pawn.",
+        player1: (0, 4),
+        player2: (8, 4),
+        walls: &[],
+        walls_in_hand: 0,
+        highlights: &[Move::Step(1, 4), Move::Step(0, 3), Move::Step(0, 5)],
+        action: Action::AnyStep,
+    },
+    Step {
+        title: "Jumping",
+        text: "When the pawns face each other, you can jump over the other pawn, as long as there's no wall behind it.\n\nJump to the square behind the purple pawn.",
+        player1: (3, 4),
+        player2: (4, 4),
+        walls: &[],
+        walls_in_hand: 0,
+        highlights: &[Move::Step(5, 4)],
+        action: Action::StepTo(5, 4),
+    },
+    Step {
+        title: "Blocked jumps",
+        text: "A wall behind the other pawn stops the jump, so you have to go around.\n\nThe wall behind the purple pawn blocks the jump. Walk around it: step up or down.",
+        player1: (3, 4),
+        player2: (4, 4),
+        walls: &[Move::Vertical(5, 3)],
+        walls_in_hand: 0,
+        highlights: &[Move::Step(3, 3), Move::Step(3, 5)],
+        action: Action::AnyStep,
+    },
+    Step {
+        title: "Walls",
+        text: "Instead of moving, you can place a wall in the gap between the squares. A wall is two squares long, and pawns can't go through it. You have 10 walls in a game; the bars beside the board are the ones left.\n\nClick a gap to place a wall, like the one in front of the purple pawn.",
+        player1: (2, 4),
+        player2: (6, 4),
+        walls: &[],
+        walls_in_hand: 3,
+        highlights: &[Move::Vertical(6, 3)],
+        action: Action::AnyWall,
+    },
+    Step {
+        title: "No trapping",
+        text: "A wall may make the way longer, but it may never cut a pawn off from its goal completely.\n\nThe purple pawn is in a corner with a single way out. Try to close it with the highlighted wall.",
+        player1: (2, 4),
+        player2: (8, 0),
+        walls: &[Move::Horizontal(7, 1)],
+        walls_in_hand: 3,
+        highlights: &[Move::Vertical(7, 0)],
+        action: Action::TrappingWall,
+    },
+    Step {
+        title: "The goal",
+        text: "The first pawn on its goal edge wins the game. Any square of the edge will do.\n\nStep onto the right edge to win.",
+        player1: (7, 4),
+        player2: (3, 6),
+        walls: &[],
+        walls_in_hand: 0,
+        highlights: &[
+            Move::Step(8, 0), Move::Step(8, 1), Move::Step(8, 2), Move::Step(8, 3), Move::Step(8, 4),
+            Move::Step(8, 5), Move::Step(8, 6), Move::Step(8, 7), Move::Step(8, 8),
+        ],
+        action: Action::Goal,
+    },
+];
+
+const LAST_PAGE: &str = "Those are all the rules.\n\nPlay a game against the CPU, or try the puzzles. \"Back\" returns to the menu.";